@@ -0,0 +1,497 @@
+use crate::commands::tasks::can_parse::{
+    CanParseFileResult, ConformanceResults, FailureDetail, ResultsTable,
+};
+use crate::commands::tasks::deserialization::{Clock, FileTestResult, PerformanceTable};
+use crate::core::config::TaskType;
+use crate::core::executor::Outcome;
+use crate::core::savefile::Game;
+use crate::core::stats;
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// What counts as a regression against a stored run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegressionGate {
+    /// Results file of the stored run
+    pub baseline: PathBuf,
+    /// Largest allowed drop in a save file's median throughput, as a fraction
+    pub max_slowdown: Option<f64>,
+    /// Whether files that passed in the baseline may fail now
+    pub conformance: bool,
+}
+
+impl RegressionGate {
+    /// The gate described by the task's command line options, if a baseline
+    /// was given
+    pub fn from_args(
+        task: TaskType,
+        baseline: Option<PathBuf>,
+        max_slowdown: Option<f64>,
+        no_conformance_regression: bool,
+    ) -> Result<Option<RegressionGate>> {
+        let Some(baseline) = baseline else {
+            return Ok(None);
+        };
+        if max_slowdown.is_none() && !no_conformance_regression {
+            let thresholds = match task {
+                TaskType::Deserialization => "--max-slowdown or --no-conformance-regression",
+                TaskType::CanParse | TaskType::Localisation => "--no-conformance-regression",
+            };
+            anyhow::bail!(
+                "--baseline needs a threshold to check the {} task against: {}",
+                task,
+                thresholds
+            );
+        }
+        // Checked up front so a long run isn't wasted on a mistyped path
+        anyhow::ensure!(
+            baseline.is_file(),
+            "Baseline results file {} doesn't exist",
+            baseline.display()
+        );
+
+        Ok(Some(RegressionGate {
+            baseline,
+            max_slowdown,
+            conformance: no_conformance_regression,
+        }))
+    }
+}
+
+/// Something that got worse since the baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub implementation: String,
+    pub game: Option<Game>,
+    /// Save or corpus file that got worse, if it was a single file
+    pub file: Option<String>,
+    pub description: String,
+}
+
+impl std::fmt::Display for Regression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.implementation)?;
+        if let Some(game) = self.game {
+            write!(f, " {}", game)?;
+        }
+        if let Some(file) = &self.file {
+            write!(f, " ({})", file)?;
+        }
+        write!(f, ": {}", self.description)
+    }
+}
+
+/// Parse a percentage such as `10%` or `10` into a fraction
+pub fn parse_percentage(s: &str) -> Result<f64, String> {
+    match s.trim_end_matches('%').parse::<f64>() {
+        Ok(x) if (0.0..=100.0).contains(&x) => Ok(x / 100.0),
+        _ => Err(String::from("must be a percentage from 0 to 100, like 10%")),
+    }
+}
+
+/// Save files whose median throughput dropped by more than the gate allows,
+/// and, when conformance regressions are checked, save files that ran
+/// successfully in the baseline but fail now
+pub fn benchmark_regressions(
+    baseline: &PerformanceTable,
+    current: &PerformanceTable,
+    gate: &RegressionGate,
+    clock: Clock,
+) -> Vec<Regression> {
+    let baseline_files: HashMap<(&str, Game, &str), _> = baseline
+        .files
+        .iter()
+        .map(|x| ((x.implementation.as_str(), x.game, x.file_name.as_str()), x))
+        .collect();
+
+    let mut regressions = Vec::new();
+    for file in &current.files {
+        let key = (
+            file.implementation.as_str(),
+            file.game,
+            file.file_name.as_str(),
+        );
+        let Some(before) = baseline_files.get(&key) else {
+            continue;
+        };
+        if before.result.outcome() != Outcome::Success {
+            continue;
+        }
+
+        let regression = |description: String| Regression {
+            implementation: file.implementation.clone(),
+            game: Some(file.game),
+            file: Some(file.file_name.clone()),
+            description,
+        };
+
+        if let FileTestResult::Failed { outcome, error } = &file.result {
+            if gate.conformance {
+                regressions.push(regression(format!("now fails ({}): {}", outcome, error)));
+            }
+            continue;
+        }
+
+        let Some(max_slowdown) = gate.max_slowdown else {
+            continue;
+        };
+        let median = |x: &[f64]| stats::percentile(x, 50.0);
+        let (Some(before), Some(after)) = (
+            median(&before.throughputs(clock)),
+            median(&file.throughputs(clock)),
+        ) else {
+            continue;
+        };
+
+        let slowdown = if before > 0.0 {
+            1.0 - after / before
+        } else {
+            0.0
+        };
+        if slowdown > max_slowdown {
+            regressions.push(regression(format!(
+                "{} throughput fell {:.1}% from {:.1} MB/s to {:.1} MB/s",
+                clock,
+                slowdown * 100.0,
+                before,
+                after
+            )));
+        }
+    }
+    regressions
+}
+
+/// The corpus files run for each implementation and game, and which of them
+/// failed
+#[derive(Default)]
+struct ConformanceRun<'a> {
+    files: HashMap<(&'a str, Game), HashSet<&'a str>>,
+    failed: HashSet<(&'a str, Game, &'a str)>,
+}
+
+impl<'a> ConformanceRun<'a> {
+    fn new(results: &'a [CanParseFileResult], failures: &'a [FailureDetail]) -> Self {
+        let files = results
+            .iter()
+            .map(|x| {
+                let files = x.corpus_files.iter().map(String::as_str).collect();
+                ((x.implementation.as_str(), x.game), files)
+            })
+            .collect();
+        let failed = failures
+            .iter()
+            .flat_map(|x| {
+                x.games
+                    .iter()
+                    .map(|game| (x.implementation.as_str(), *game, x.corpus_file.as_str()))
+            })
+            .collect();
+        ConformanceRun { files, failed }
+    }
+
+    /// Whether the file was run for the implementation and game and passed
+    fn passed(&self, implementation: &str, game: Game, file: &str) -> bool {
+        self.files
+            .get(&(implementation, game))
+            .is_some_and(|files| files.contains(file))
+            && !self.failed.contains(&(implementation, game, file))
+    }
+}
+
+/// Games whose success rate over the corpus files in both runs dropped, and
+/// corpus files that fail for a game now but passed in the baseline. Files
+/// that weren't run in the baseline, like newly added ones, can't regress.
+pub fn conformance_regressions(
+    baseline: &ConformanceResults,
+    current: &ResultsTable,
+) -> Vec<Regression> {
+    let before = ConformanceRun::new(&baseline.results, &baseline.failures);
+    let after = ConformanceRun::new(&current.results, &current.failures);
+
+    let mut regressions = Vec::new();
+    for result in &current.results {
+        let key = (result.implementation.as_str(), result.game);
+        let (Some(before_files), Some(after_files)) =
+            (before.files.get(&key), after.files.get(&key))
+        else {
+            continue;
+        };
+        let shared: Vec<&str> = result
+            .corpus_files
+            .iter()
+            .map(String::as_str)
+            .filter(|file| before_files.contains(file) && after_files.contains(file))
+            .collect();
+        if shared.is_empty() {
+            continue;
+        }
+
+        let rate = |run: &ConformanceRun| {
+            let passed = shared
+                .iter()
+                .filter(|file| run.passed(key.0, key.1, file))
+                .count();
+            passed as f64 / shared.len() as f64 * 100.0
+        };
+        let (rate_before, rate_after) = (rate(&before), rate(&after));
+        if rate_after < rate_before {
+            regressions.push(Regression {
+                implementation: result.implementation.clone(),
+                game: Some(result.game),
+                file: None,
+                description: format!(
+                    "success rate over {} corpus files in both runs fell from {:.1}% to {:.1}%",
+                    shared.len(),
+                    rate_before,
+                    rate_after
+                ),
+            });
+        }
+    }
+
+    for failure in &current.failures {
+        let error = failure.error_message.lines().next().unwrap_or_default();
+        for game in &failure.games {
+            let implementation = failure.implementation.as_str();
+            let file = failure.corpus_file.as_str();
+            // A failure outside the corpus, like the build, stops the whole game
+            let stopped = !after.files.contains_key(&(implementation, *game))
+                && before.files.contains_key(&(implementation, *game));
+            if !stopped && !before.passed(implementation, *game, file) {
+                continue;
+            }
+
+            regressions.push(Regression {
+                implementation: failure.implementation.clone(),
+                game: Some(*game),
+                file: Some(failure.corpus_file.clone()),
+                description: format!("now fails: {}", error),
+            });
+        }
+    }
+    regressions
+}
+
+/// Check a conformance run against the baseline, failing if anything regressed
+pub fn check_conformance(
+    gate: &RegressionGate,
+    task: TaskType,
+    table: &ResultsTable,
+) -> Result<()> {
+    if !gate.conformance {
+        return Ok(());
+    }
+    let baseline = ConformanceResults::load(&gate.baseline, task)?;
+    report(&gate.baseline, &conformance_regressions(&baseline, table))
+}
+
+/// Check a deserialization run against the baseline, failing if anything
+/// regressed
+pub fn check_benchmarks(
+    gate: &RegressionGate,
+    table: &PerformanceTable,
+    clock: Clock,
+) -> Result<()> {
+    let baseline = PerformanceTable::load(&gate.baseline)?;
+    report(
+        &gate.baseline,
+        &benchmark_regressions(&baseline, table, gate, clock),
+    )
+}
+
+/// Print the regressions, and fail if there are any
+fn report(baseline: &Path, regressions: &[Regression]) -> Result<()> {
+    println!();
+    if regressions.is_empty() {
+        println!("No regressions against {}", baseline.display());
+        return Ok(());
+    }
+
+    println!("Regressions against {}:", baseline.display());
+    for regression in regressions {
+        println!("  {}", regression);
+    }
+    anyhow::bail!(
+        "{} regression(s) against {}",
+        regressions.len(),
+        baseline.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tasks::deserialization::{FileResult, RunMeasurement, Timings};
+    use std::time::Duration;
+
+    fn gate(max_slowdown: Option<f64>, conformance: bool) -> RegressionGate {
+        RegressionGate {
+            baseline: PathBuf::from("baseline.json"),
+            max_slowdown,
+            conformance,
+        }
+    }
+
+    fn benchmarks(files: &[(&str, Option<u64>)]) -> PerformanceTable {
+        let files = files
+            .iter()
+            .map(|(name, millis)| FileResult {
+                game: Game::Eu4,
+                implementation: String::from("jomini-tape"),
+                file_name: name.to_string(),
+                data_size_bytes: 1024 * 1024,
+                result: match millis {
+                    Some(millis) => FileTestResult::Success {
+                        runs: vec![RunMeasurement {
+                            timings: Timings {
+                                self_reported: Duration::from_millis(*millis),
+                                wall: Duration::from_millis(*millis),
+                                cpu: None,
+                            },
+                            usage: None,
+                            phases: Vec::new(),
+                            warnings: Vec::new(),
+                        }],
+                    },
+                    None => FileTestResult::Failed {
+                        outcome: Outcome::Crashed,
+                        error: String::from("SIGSEGV"),
+                    },
+                },
+            })
+            .collect();
+        PerformanceTable {
+            files,
+            implementations: vec![String::from("jomini-tape")],
+            conditions: None,
+        }
+    }
+
+    #[test]
+    fn test_gate_from_args() {
+        let baseline = || Some(PathBuf::from("baseline.json"));
+        assert_eq!(
+            RegressionGate::from_args(TaskType::CanParse, None, None, false).unwrap(),
+            None
+        );
+
+        // Only the thresholds the task accepts are suggested
+        let error = RegressionGate::from_args(TaskType::CanParse, baseline(), None, false)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.ends_with(": --no-conformance-regression"),
+            "{}",
+            error
+        );
+        let error = RegressionGate::from_args(TaskType::Deserialization, baseline(), None, false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("--max-slowdown"), "{}", error);
+    }
+
+    #[test]
+    fn test_parse_percentage() {
+        assert_eq!(parse_percentage("10%"), Ok(0.1));
+        assert_eq!(parse_percentage("25"), Ok(0.25));
+        assert!(parse_percentage("150%").is_err());
+        assert!(parse_percentage("fast").is_err());
+    }
+
+    #[test]
+    fn test_benchmark_regressions() {
+        let baseline = benchmarks(&[("a.eu4", Some(100)), ("b.eu4", Some(100)), ("c.eu4", None)]);
+        let current = benchmarks(&[("a.eu4", Some(105)), ("b.eu4", Some(200)), ("c.eu4", None)]);
+
+        let regressions =
+            benchmark_regressions(&baseline, &current, &gate(Some(0.1), false), Clock::Wall);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].file.as_deref(), Some("b.eu4"));
+        assert_eq!(
+            regressions[0].to_string(),
+            "jomini-tape eu4 (b.eu4): wall throughput fell 50.0% from 10.0 MB/s to 5.0 MB/s"
+        );
+
+        // Files that stopped running successfully are conformance regressions
+        let current = benchmarks(&[("a.eu4", None), ("b.eu4", Some(100))]);
+        let regressions =
+            benchmark_regressions(&baseline, &current, &gate(None, true), Clock::Wall);
+        assert_eq!(regressions.len(), 1);
+        assert!(regressions[0]
+            .description
+            .starts_with("now fails (crashed)"));
+        assert!(
+            benchmark_regressions(&baseline, &current, &gate(Some(0.1), false), Clock::Wall)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_conformance_regressions() {
+        let result = |implementation: &str, game: Game, corpus_files: &[&str]| CanParseFileResult {
+            implementation: implementation.to_string(),
+            game,
+            success_rate: 0.0,
+            lenient_success_rate: 0.0,
+            timed_out_tests: 0,
+            corpus_files: corpus_files.iter().map(|x| x.to_string()).collect(),
+        };
+        let failure = |implementation: &str, corpus_file: &str, games: &[Game]| FailureDetail {
+            implementation: implementation.to_string(),
+            corpus_file: corpus_file.to_string(),
+            games: games.to_vec(),
+            error_message: String::from("Expected acceptance\nstderr: oops"),
+            notes: Vec::new(),
+        };
+
+        let baseline = ConformanceResults {
+            results: vec![
+                result("jomini-tape", Game::Eu4, &["a.txt", "b.txt", "old.txt"]),
+                result("jomini-tape", Game::Ck3, &["a.txt", "old.txt"]),
+                result("jomini-reader", Game::Eu4, &["a.txt"]),
+            ],
+            failures: vec![failure("jomini-tape", "old.txt", &[Game::Eu4])],
+        };
+        let mut current = ResultsTable::new(vec![
+            String::from("jomini-tape"),
+            String::from("jomini-reader"),
+            String::from("pdx"),
+        ]);
+        current.results = vec![
+            result(
+                "jomini-tape",
+                Game::Eu4,
+                &["a.txt", "b.txt", "new.txt", "old.txt"],
+            ),
+            result("jomini-tape", Game::Ck3, &["a.txt", "old.txt"]),
+            result("pdx", Game::Eu4, &["a.txt"]),
+        ];
+        current.failures = vec![
+            // Failing for another game is a regression for that game
+            failure("jomini-tape", "old.txt", &[Game::Eu4, Game::Ck3]),
+            failure("jomini-tape", "b.txt", &[Game::Eu4]),
+            // Files added since the baseline can't regress
+            failure("jomini-tape", "new.txt", &[Game::Eu4]),
+            // Nor can implementations missing from the baseline
+            failure("pdx", "a.txt", &[Game::Eu4]),
+            // But failing to build stops every game of the baseline
+            failure("jomini-reader", "build", &[Game::Eu4]),
+        ];
+
+        let regressions: Vec<String> = conformance_regressions(&baseline, &current)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            regressions,
+            [
+                "jomini-tape eu4: success rate over 3 corpus files in both runs fell from 66.7% to 33.3%",
+                "jomini-tape ck3: success rate over 2 corpus files in both runs fell from 100.0% to 50.0%",
+                "jomini-tape ck3 (old.txt): now fails: Expected acceptance",
+                "jomini-tape eu4 (b.txt): now fails: Expected acceptance",
+                "jomini-reader eu4 (build): now fails: Expected acceptance",
+            ]
+        );
+    }
+}
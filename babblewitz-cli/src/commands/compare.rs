@@ -0,0 +1,373 @@
+use crate::commands::tasks::deserialization::{
+    self, Clock, FileResult, PerformanceTable, RunOptions, ThroughputStats,
+};
+use crate::core::executor::Outcome;
+use crate::core::implementation::Implementation;
+use crate::core::savefile::Game;
+use crate::core::stats::{self, MannWhitney};
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Bootstrap resamples drawn for each confidence interval
+const BOOTSTRAP_RESAMPLES: usize = 10_000;
+
+/// One side of a comparison
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// Implementation directory to benchmark
+    Implementation(PathBuf),
+    /// Results file written by `task deserialization --output`, and which of
+    /// its implementations to compare when it has several
+    Results {
+        path: PathBuf,
+        implementation: Option<String>,
+    },
+}
+
+impl std::str::FromStr for Source {
+    type Err = std::convert::Infallible;
+
+    /// Directories are implementations and anything else a results file,
+    /// optionally followed by `#<implementation>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let path = PathBuf::from(s);
+        if path.is_dir() {
+            return Ok(Source::Implementation(path));
+        }
+
+        match s.rsplit_once('#') {
+            Some((file, name)) if !path.exists() => Ok(Source::Results {
+                path: PathBuf::from(file),
+                implementation: Some(name.to_string()),
+            }),
+            _ => Ok(Source::Results {
+                path,
+                implementation: None,
+            }),
+        }
+    }
+}
+
+/// How two implementations' throughput on the same saves compares
+#[derive(Debug)]
+pub struct Comparison {
+    pub baseline: String,
+    pub candidate: String,
+    pub clock: Clock,
+    pub confidence: f64,
+    pub games: Vec<GameComparison>,
+}
+
+#[derive(Debug)]
+pub struct GameComparison {
+    pub game: Game,
+    /// Save files that both implementations ran successfully
+    pub files: usize,
+    pub baseline: ThroughputStats,
+    pub candidate: ThroughputStats,
+    /// Median throughput of the candidate relative to the baseline
+    pub speedup: f64,
+    /// Confidence interval of the speedup
+    pub interval: (f64, f64),
+    pub test: MannWhitney,
+}
+
+impl GameComparison {
+    /// Whether the difference is unlikely to be noise at the given confidence
+    pub fn is_significant(&self, confidence: f64) -> bool {
+        self.test.p_value < 1.0 - confidence
+    }
+}
+
+/// Compare the candidate's throughput with the baseline's, benchmarking
+/// either side that is an implementation
+pub fn run_comparison(
+    baseline: &Source,
+    candidate: &Source,
+    options: RunOptions,
+    clock: Clock,
+    confidence: f64,
+) -> Result<Comparison> {
+    let (baseline, baseline_files) = load_source(baseline, options)?;
+    let (candidate, candidate_files) = load_source(candidate, options)?;
+
+    Ok(Comparison {
+        games: compare_files(&baseline_files, &candidate_files, clock, confidence),
+        baseline,
+        candidate,
+        clock,
+        confidence,
+    })
+}
+
+/// The name and file results of one side of a comparison
+fn load_source(source: &Source, options: RunOptions) -> Result<(String, Vec<FileResult>)> {
+    match source {
+        Source::Implementation(path) => {
+            let implementation = Implementation::load_from_path(path)?;
+            let table = deserialization::run_benchmark_table_for(
+                std::slice::from_ref(&implementation),
+                options,
+            )?;
+            Ok((implementation.name, table.files))
+        }
+        Source::Results {
+            path,
+            implementation,
+        } => {
+            let table = PerformanceTable::load(path)?;
+            let name = match (implementation, table.implementations.as_slice()) {
+                (Some(name), names) if names.contains(name) => name.clone(),
+                (None, [name]) => name.clone(),
+                (_, names) => anyhow::bail!(
+                    "{} has results for {}; choose one with {}#<name>",
+                    path.display(),
+                    names.join(", "),
+                    path.display()
+                ),
+            };
+
+            let files = table
+                .files
+                .into_iter()
+                .filter(|x| x.implementation == name)
+                .collect();
+            Ok((name, files))
+        }
+    }
+}
+
+/// Compare the throughput of each game's save files that both sides ran
+/// successfully. Games without such files are left out.
+pub fn compare_files(
+    baseline: &[FileResult],
+    candidate: &[FileResult],
+    clock: Clock,
+    confidence: f64,
+) -> Vec<GameComparison> {
+    let candidate_files: HashMap<(Game, &str), &FileResult> = candidate
+        .iter()
+        .map(|x| ((x.game, x.file_name.as_str()), x))
+        .collect();
+
+    // Pair up each game's samples from the files both sides ran
+    let mut samples: BTreeMap<Game, (usize, Vec<f64>, Vec<f64>)> = BTreeMap::new();
+    for file in baseline {
+        let Some(other) = candidate_files.get(&(file.game, file.file_name.as_str())) else {
+            continue;
+        };
+        if file.result.outcome() != Outcome::Success || other.result.outcome() != Outcome::Success {
+            continue;
+        }
+
+        let (baseline_samples, candidate_samples) =
+            (file.throughputs(clock), other.throughputs(clock));
+        if baseline_samples.is_empty() || candidate_samples.is_empty() {
+            continue;
+        }
+
+        let entry = samples.entry(file.game).or_default();
+        entry.0 += 1;
+        entry.1.extend(baseline_samples);
+        entry.2.extend(candidate_samples);
+    }
+
+    samples
+        .into_iter()
+        .filter_map(|(game, (files, baseline, candidate))| {
+            let baseline_stats = ThroughputStats::of(&baseline)?;
+            let candidate_stats = ThroughputStats::of(&candidate)?;
+            Some(GameComparison {
+                game,
+                files,
+                speedup: candidate_stats.median / baseline_stats.median,
+                interval: stats::bootstrap_median_ratio(
+                    &baseline,
+                    &candidate,
+                    confidence,
+                    BOOTSTRAP_RESAMPLES,
+                )?,
+                test: stats::mann_whitney_u(&baseline, &candidate)?,
+                baseline: baseline_stats,
+                candidate: candidate_stats,
+            })
+        })
+        .collect()
+}
+
+/// Print the speedup of the candidate over the baseline for each game, with
+/// its confidence interval and whether the difference is significant
+pub fn print_comparison(comparison: &Comparison) {
+    let percent = comparison.confidence * 100.0;
+    println!(
+        "Comparing {} (candidate) with {} (baseline) by {} time",
+        comparison.candidate, comparison.baseline, comparison.clock
+    );
+
+    if comparison.games.is_empty() {
+        println!("No save files were run successfully by both implementations.");
+        return;
+    }
+
+    let interval_label = format!("{}% CI", percent);
+    println!(
+        "{:<10} {:>6} {:>6} {:>12} {:>12} {:>8} {:>18} {:>8}  RESULT",
+        "GAME", "FILES", "RUNS", "BASELINE", "CANDIDATE", "SPEEDUP", interval_label, "P-VALUE"
+    );
+    for game in &comparison.games {
+        let result = if !game.is_significant(comparison.confidence) {
+            "no significant difference"
+        } else if game.speedup > 1.0 {
+            "faster"
+        } else {
+            "slower"
+        };
+
+        println!(
+            "{:<10} {:>6} {:>6} {:>12} {:>12} {:>8} {:>18} {:>8}  {}",
+            game.game.to_string(),
+            game.files,
+            game.baseline.runs.min(game.candidate.runs),
+            format!("{:.1} MB/s", game.baseline.median),
+            format!("{:.1} MB/s", game.candidate.median),
+            format_speedup(game.speedup),
+            format!(
+                "{} - {}",
+                format_speedup(game.interval.0),
+                format_speedup(game.interval.1)
+            ),
+            format_p_value(game.test.p_value),
+            result
+        );
+    }
+
+    if comparison.games.iter().any(|x| x.baseline.runs < 5) {
+        println!();
+        println!("Few runs were measured; use --iterations to collect more samples.");
+    }
+}
+
+fn format_speedup(ratio: f64) -> String {
+    format!("{:.3}x", ratio)
+}
+
+fn format_p_value(p_value: f64) -> String {
+    if p_value < 0.001 {
+        String::from("<0.001")
+    } else {
+        format!("{:.3}", p_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tasks::deserialization::{FileTestResult, RunMeasurement, Timings};
+    use std::time::Duration;
+
+    fn file(implementation: &str, name: &str, micros: &[u64]) -> FileResult {
+        let runs = micros
+            .iter()
+            .map(|x| RunMeasurement {
+                timings: Timings {
+                    self_reported: Duration::from_micros(*x),
+                    wall: Duration::from_micros(*x),
+                    cpu: None,
+                },
+                usage: None,
+                phases: Vec::new(),
+                warnings: Vec::new(),
+            })
+            .collect();
+        FileResult {
+            game: Game::Eu4,
+            implementation: implementation.to_string(),
+            file_name: name.to_string(),
+            data_size_bytes: 1024 * 1024,
+            result: FileTestResult::Success { runs },
+        }
+    }
+
+    #[test]
+    fn test_compare_files() {
+        let slow: Vec<u64> = (0..20).map(|x| 2000 + x * 10).collect();
+        let fast: Vec<u64> = (0..20).map(|x| 1000 + x * 5).collect();
+        let baseline = [file("a", "a.eu4", &slow), file("a", "b.eu4", &slow)];
+        let candidate = [
+            file("b", "a.eu4", &fast),
+            // Files that only one side ran are left out
+            file("b", "c.eu4", &fast),
+        ];
+
+        let games = compare_files(&baseline, &candidate, Clock::SelfReported, 0.95);
+        assert_eq!(games.len(), 1);
+        let eu4 = &games[0];
+        assert_eq!(eu4.files, 1);
+        assert!((eu4.speedup - 2.0).abs() < 0.01, "{}", eu4.speedup);
+        assert!(eu4.interval.0 <= eu4.speedup && eu4.speedup <= eu4.interval.1);
+        assert!(eu4.is_significant(0.95));
+
+        // The same samples on both sides are indistinguishable
+        let games = compare_files(&baseline, &baseline, Clock::SelfReported, 0.95);
+        assert_eq!(games[0].speedup, 1.0);
+        assert!(!games[0].is_significant(0.95));
+    }
+
+    #[test]
+    fn test_source_from_str() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_arg = dir.path().to_str().unwrap();
+        assert_eq!(
+            dir_arg.parse::<Source>().unwrap(),
+            Source::Implementation(dir.path().to_path_buf())
+        );
+        assert_eq!(
+            "results.json#jomini-tape".parse::<Source>().unwrap(),
+            Source::Results {
+                path: PathBuf::from("results.json"),
+                implementation: Some(String::from("jomini-tape")),
+            }
+        );
+        assert_eq!(
+            "results.json".parse::<Source>().unwrap(),
+            Source::Results {
+                path: PathBuf::from("results.json"),
+                implementation: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_load_source_chooses_implementation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.json");
+        let table = PerformanceTable {
+            files: vec![file("a", "a.eu4", &[1000]), file("b", "a.eu4", &[2000])],
+            implementations: vec![String::from("a"), String::from("b")],
+            conditions: None,
+        };
+        table.save(&path).unwrap();
+        let options = RunOptions {
+            jobs: 1,
+            iterations: 1,
+            warmup: 0,
+            isolate_cpu: None,
+        };
+
+        let source = Source::Results {
+            path: path.clone(),
+            implementation: Some(String::from("b")),
+        };
+        let (name, files) = load_source(&source, options).unwrap();
+        assert_eq!(name, "b");
+        assert_eq!(files.len(), 1);
+
+        let source = Source::Results {
+            path,
+            implementation: None,
+        };
+        let error = load_source(&source, options).unwrap_err().to_string();
+        assert!(error.contains("a, b"), "{}", error);
+    }
+}
@@ -0,0 +1,681 @@
+use crate::core::config::TaskType;
+use crate::core::corpus::{self, DirectiveError};
+use crate::core::implementation::find_all_implementations;
+use crate::core::savefile::Game;
+use anyhow::Result;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    pub path: PathBuf,
+    /// 1-based line number, if the issue is tied to a line
+    pub line: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl std::fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: ", self.path.display(), line)?,
+            None => write!(f, "{}: ", self.path.display())?,
+        }
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Lint the corpus of each conformance task, printing every issue found.
+/// Fails if any issue is an error.
+pub fn run_lint() -> Result<()> {
+    let implementations = find_all_implementations()?;
+
+    let mut issues = Vec::new();
+    for task in [TaskType::CanParse, TaskType::Localisation] {
+        let claimed_games: HashSet<Game> = implementations
+            .iter()
+            .flat_map(|implementation| implementation.games_for_task(task))
+            .collect();
+
+        let corpus_dir = corpus::task_corpus_dir(task);
+        if corpus_dir.exists() {
+            issues.extend(lint_corpus(&corpus_dir, &claimed_games)?);
+        }
+    }
+
+    for issue in &issues {
+        println!("{}", issue);
+    }
+
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+    println!(
+        "Corpus lint finished: {} error(s), {} warning(s)",
+        errors, warnings
+    );
+
+    anyhow::ensure!(errors == 0, "Corpus lint found {} error(s)", errors);
+    Ok(())
+}
+
+/// Lint every corpus file under the corpus directory
+pub fn lint_corpus(corpus_dir: &Path, claimed_games: &HashSet<Game>) -> Result<Vec<LintIssue>> {
+    let mut issues = Vec::new();
+    let mut by_directory: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+
+    for entry in WalkDir::new(corpus_dir).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path().to_path_buf();
+        if corpus::is_expectation_file(&path) {
+            lint_expectation_file(&path, &mut issues);
+            continue;
+        }
+
+        lint_file(&path, claimed_games, &mut issues)?;
+        let directory = path.parent().unwrap_or(corpus_dir).to_path_buf();
+        by_directory.entry(directory).or_default().push(path);
+    }
+
+    for files in by_directory.values() {
+        lint_numeric_prefixes(files, &mut issues);
+    }
+
+    issues.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+    Ok(issues)
+}
+
+/// Lint a single corpus file
+fn lint_file(
+    path: &Path,
+    claimed_games: &HashSet<Game>,
+    issues: &mut Vec<LintIssue>,
+) -> Result<()> {
+    let mut report = |line: Option<usize>, severity: Severity, message: String| {
+        issues.push(LintIssue {
+            path: path.to_path_buf(),
+            line,
+            severity,
+            message,
+        });
+    };
+
+    let raw = std::fs::read(path)?;
+    lint_line_endings(&raw, &mut report);
+    lint_final_newline(&raw, &mut report);
+
+    let corpus_files = match corpus::parse_corpus_file(path) {
+        Ok(corpus_files) => corpus_files,
+        Err(e) => {
+            let line = e.downcast_ref::<DirectiveError>().map(|e| e.line);
+            let message = match e.downcast_ref::<DirectiveError>() {
+                Some(e) => e.message.clone(),
+                None => format!("{:#}", e),
+            };
+            report(line, Severity::Error, message);
+            return Ok(());
+        }
+    };
+
+    let (directives, _) = corpus::header_directives(&raw);
+    let games_line = directives.iter().find_map(|(line, directive)| {
+        let (key, _) = directive.split_once(':')?;
+        (key.trim() == "games").then_some(*line)
+    });
+
+    // Cases inheriting the file's games shouldn't repeat the same warning
+    let all_games = corpus::expand_game_aliases("all")?;
+    let mut warned = HashSet::new();
+
+    for corpus_file in &corpus_files {
+        let warning_line = match corpus_file.case {
+            Some(_) => corpus_file.line,
+            None => games_line.unwrap_or(corpus_file.line),
+        };
+
+        if corpus_file.games.is_empty() {
+            report(
+                Some(corpus_file.line),
+                Severity::Error,
+                String::from("Missing games directive"),
+            );
+        } else if corpus_file.games != all_games {
+            // The "all" alias is expected to cover games without implementations
+            let unclaimed: Vec<&str> = corpus_file
+                .games
+                .iter()
+                .filter(|game| !claimed_games.contains(game))
+                .map(|game| game.as_str())
+                .collect();
+            if !unclaimed.is_empty() && warned.insert(unclaimed.clone()) {
+                report(
+                    Some(warning_line),
+                    Severity::Warning,
+                    format!("No implementation claims: {}", unclaimed.join(" ")),
+                );
+            }
+        }
+
+        let unlisted: Vec<&str> = corpus_file
+            .since
+            .iter()
+            .filter(|(game, _)| !corpus_file.games.contains(game))
+            .map(|(game, _)| game.as_str())
+            .collect();
+        if !unlisted.is_empty() {
+            report(
+                Some(corpus_file.line),
+                Severity::Warning,
+                format!(
+                    "Version constraint for unlisted game: {}",
+                    unlisted.join(" ")
+                ),
+            );
+        }
+
+        if corpus_file.content.trim_ascii().is_empty() {
+            report(
+                Some(corpus_file.content_line),
+                Severity::Error,
+                String::from("Content is empty after stripping directives"),
+            );
+        }
+
+        if corpus_file.encoding.is_none() {
+            if let Some(position) = corpus_file.content.iter().position(|b| !b.is_ascii()) {
+                let line = corpus_file.content_line + line_of(&corpus_file.content, position) - 1;
+                report(
+                    Some(line),
+                    Severity::Error,
+                    String::from("Non-ASCII content without an encoding directive"),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Report files that mix line ending styles
+fn lint_line_endings(raw: &[u8], report: &mut impl FnMut(Option<usize>, Severity, String)) {
+    let mut first_style = None;
+    let mut line = 1;
+    let mut i = 0;
+    while i < raw.len() {
+        let style = match raw[i] {
+            b'\r' if raw.get(i + 1) == Some(&b'\n') => {
+                i += 1;
+                "CRLF"
+            }
+            b'\r' => "CR",
+            b'\n' => "LF",
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        match first_style {
+            None => first_style = Some(style),
+            Some(first) if first != style => {
+                report(
+                    Some(line),
+                    Severity::Error,
+                    format!("Inconsistent line endings: {} after {}", style, first),
+                );
+                return;
+            }
+            Some(_) => {}
+        }
+
+        line += 1;
+        i += 1;
+    }
+}
+
+/// Report files that don't end with exactly one line ending
+fn lint_final_newline(raw: &[u8], report: &mut impl FnMut(Option<usize>, Severity, String)) {
+    let Some(rest) = strip_line_ending(raw) else {
+        if !raw.is_empty() {
+            report(
+                Some(line_of(raw, raw.len())),
+                Severity::Warning,
+                String::from("Missing final newline"),
+            );
+        }
+        return;
+    };
+
+    if strip_line_ending(rest).is_some() {
+        report(
+            Some(line_of(raw, rest.len())),
+            Severity::Warning,
+            String::from("Multiple final newlines"),
+        );
+    }
+}
+
+/// The content before a trailing CRLF, LF, or CR, if there is one
+fn strip_line_ending(raw: &[u8]) -> Option<&[u8]> {
+    raw.strip_suffix(b"\r\n")
+        .or_else(|| raw.strip_suffix(b"\n"))
+        .or_else(|| raw.strip_suffix(b"\r"))
+}
+
+/// Report expectation sidecars that aren't paired with a corpus file
+fn lint_expectation_file(path: &Path, issues: &mut Vec<LintIssue>) {
+    let paired = path
+        .parent()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .any(|x| !corpus::is_expectation_file(&x) && corpus::expected_path(&x) == path);
+
+    if !paired {
+        issues.push(LintIssue {
+            path: path.to_path_buf(),
+            line: None,
+            severity: Severity::Warning,
+            message: String::from("Expectation file has no matching corpus file"),
+        });
+    }
+}
+
+/// Report corpus files in a directory whose numeric prefixes are missing,
+/// duplicated, or skip numbers
+fn lint_numeric_prefixes(files: &[PathBuf], issues: &mut Vec<LintIssue>) {
+    let mut numbered: BTreeMap<u32, Vec<&PathBuf>> = BTreeMap::new();
+    for path in files {
+        match numeric_prefix(path) {
+            Some(number) => numbered.entry(number).or_default().push(path),
+            None => issues.push(LintIssue {
+                path: path.clone(),
+                line: None,
+                severity: Severity::Error,
+                message: String::from("Missing numeric prefix (eg: 033-name.txt)"),
+            }),
+        }
+    }
+
+    let mut expected = 1;
+    for (number, paths) in &numbered {
+        if paths.len() > 1 {
+            for path in paths {
+                issues.push(LintIssue {
+                    path: (*path).clone(),
+                    line: None,
+                    severity: Severity::Error,
+                    message: format!("Duplicate numeric prefix {:03}", number),
+                });
+            }
+        }
+
+        if *number != expected {
+            issues.push(LintIssue {
+                path: paths[0].clone(),
+                line: None,
+                severity: Severity::Error,
+                message: format!(
+                    "Non-sequential numeric prefix {:03} (expected {:03})",
+                    number, expected
+                ),
+            });
+        }
+        expected = number + 1;
+    }
+}
+
+/// The number a corpus file name starts with (`013-keys.txt` -> 13)
+fn numeric_prefix(path: &Path) -> Option<u32> {
+    let name = path.file_name()?.to_string_lossy();
+    let (prefix, _) = name.split_once('-')?;
+    if prefix.is_empty() || !prefix.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    prefix.parse().ok()
+}
+
+/// 1-based line number of a byte offset
+fn line_of(raw: &[u8], offset: usize) -> usize {
+    let before = &raw[..offset.min(raw.len())];
+    let crlf = before.windows(2).filter(|w| w == b"\r\n").count();
+    let breaks = before.iter().filter(|&&b| b == b'\n' || b == b'\r').count();
+    breaks - crlf + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn lint(files: &[(&str, &[u8])], claimed: &[Game]) -> Vec<LintIssue> {
+        let temp_dir = TempDir::new().unwrap();
+        let corpus_dir = temp_dir.path().join("corpus");
+        for (name, content) in files {
+            let path = corpus_dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let claimed = claimed.iter().copied().collect();
+        lint_corpus(&corpus_dir, &claimed)
+            .unwrap()
+            .into_iter()
+            .map(|issue| LintIssue {
+                path: issue.path.strip_prefix(&corpus_dir).unwrap().to_path_buf(),
+                ..issue
+            })
+            .collect()
+    }
+
+    fn issue(path: &str, line: Option<usize>, severity: Severity, message: &str) -> LintIssue {
+        LintIssue {
+            path: PathBuf::from(path),
+            line,
+            severity,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_lint_clean_corpus() {
+        let issues = lint(
+            &[
+                ("game/001-a.txt", b"# @babblewitz:games: all\na=b\n"),
+                ("game/002-b.txt", b"# @babblewitz:games: eu4\r\nb=c\r\n"),
+                ("game/002-b.expected.json", b"{}"),
+            ],
+            &[Game::Eu4],
+        );
+        assert_eq!(issues, vec![]);
+    }
+
+    #[test]
+    fn test_lint_directives() {
+        let issues = lint(
+            &[
+                ("game/001-a.txt", b"a=b\n"),
+                (
+                    "game/002-b.txt",
+                    b"# @babblewitz:games: eu4\n# @babblewitz:tgas: x\na=b\n",
+                ),
+                ("game/003-c.txt", b"# @babblewitz:games:\na=b\n"),
+            ],
+            &[Game::Eu4],
+        );
+        assert_eq!(
+            issues,
+            vec![
+                issue(
+                    "game/001-a.txt",
+                    Some(1),
+                    Severity::Error,
+                    "Missing games directive"
+                ),
+                issue(
+                    "game/002-b.txt",
+                    Some(2),
+                    Severity::Error,
+                    "Unrecognized directive: 'tgas'"
+                ),
+                issue(
+                    "game/003-c.txt",
+                    Some(1),
+                    Severity::Error,
+                    "No games listed"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_numeric_prefixes() {
+        let issues = lint(
+            &[
+                ("game/001-a.txt", b"# @babblewitz:games: eu4\na=b\n"),
+                ("game/001-b.txt", b"# @babblewitz:games: eu4\na=b\n"),
+                ("game/003-c.txt", b"# @babblewitz:games: eu4\na=b\n"),
+                ("game/d.txt", b"# @babblewitz:games: eu4\na=b\n"),
+                ("other/001-a.txt", b"# @babblewitz:games: eu4\na=b\n"),
+            ],
+            &[Game::Eu4],
+        );
+        assert_eq!(
+            issues,
+            vec![
+                issue(
+                    "game/001-a.txt",
+                    None,
+                    Severity::Error,
+                    "Duplicate numeric prefix 001"
+                ),
+                issue(
+                    "game/001-b.txt",
+                    None,
+                    Severity::Error,
+                    "Duplicate numeric prefix 001"
+                ),
+                issue(
+                    "game/003-c.txt",
+                    None,
+                    Severity::Error,
+                    "Non-sequential numeric prefix 003 (expected 002)"
+                ),
+                issue(
+                    "game/d.txt",
+                    None,
+                    Severity::Error,
+                    "Missing numeric prefix (eg: 033-name.txt)"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_unclaimed_games() {
+        let issues = lint(
+            &[
+                ("game/001-a.txt", b"# @babblewitz:games: all\na=b\n"),
+                (
+                    "game/002-b.txt",
+                    b"# @babblewitz:games: eu4 ck3 vic3\na=b\n",
+                ),
+            ],
+            &[Game::Eu4],
+        );
+        assert_eq!(
+            issues,
+            vec![issue(
+                "game/002-b.txt",
+                Some(1),
+                Severity::Warning,
+                "No implementation claims: ck3 vic3"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_lint_content() {
+        let issues = lint(
+            &[
+                ("game/001-a.txt", b"# @babblewitz:games: eu4\n  \n"),
+                (
+                    "game/002-b.txt",
+                    b"# @babblewitz:games: eu4\r\n# @babblewitz:tags: x\r\na=b\nc=d\n",
+                ),
+                (
+                    "game/003-c.txt",
+                    b"# @babblewitz:games: eu4\na=b\nname=\"M\xfcnchen\"\n",
+                ),
+                (
+                    "game/004-d.txt",
+                    b"# @babblewitz:games: eu4\n# @babblewitz:encoding: utf-8\nname=\"M\xfcnchen\"\n",
+                ),
+                ("game/005-e.expected.json", b"{}"),
+            ],
+            &[Game::Eu4],
+        );
+        assert_eq!(
+            issues,
+            vec![
+                issue(
+                    "game/001-a.txt",
+                    Some(2),
+                    Severity::Error,
+                    "Content is empty after stripping directives"
+                ),
+                issue(
+                    "game/002-b.txt",
+                    Some(3),
+                    Severity::Error,
+                    "Inconsistent line endings: LF after CRLF"
+                ),
+                issue(
+                    "game/003-c.txt",
+                    Some(3),
+                    Severity::Error,
+                    "Non-ASCII content without an encoding directive"
+                ),
+                issue(
+                    "game/004-d.txt",
+                    Some(2),
+                    Severity::Error,
+                    "Declared utf-8 but content is not: invalid utf-8 sequence of 1 bytes from index 7"
+                ),
+                issue(
+                    "game/005-e.expected.json",
+                    None,
+                    Severity::Warning,
+                    "Expectation file has no matching corpus file"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_final_newline() {
+        let issues = lint(
+            &[
+                ("game/001-a.txt", b"# @babblewitz:games: eu4\na=b"),
+                ("game/002-b.txt", b"# @babblewitz:games: eu4\na=b\n\n"),
+                ("game/003-c.txt", b"# @babblewitz:games: eu4\r\na=b\r\n\r\n"),
+                ("game/004-d.txt", b"# @babblewitz:games: eu4\r\na=b\r\n"),
+            ],
+            &[Game::Eu4],
+        );
+        assert_eq!(
+            issues,
+            vec![
+                issue(
+                    "game/001-a.txt",
+                    Some(2),
+                    Severity::Warning,
+                    "Missing final newline"
+                ),
+                issue(
+                    "game/002-b.txt",
+                    Some(3),
+                    Severity::Warning,
+                    "Multiple final newlines"
+                ),
+                issue(
+                    "game/003-c.txt",
+                    Some(3),
+                    Severity::Warning,
+                    "Multiple final newlines"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_of() {
+        let raw = b"a\r\nb\nc\rd";
+        assert_eq!(line_of(raw, 0), 1);
+        assert_eq!(line_of(raw, 3), 2);
+        assert_eq!(line_of(raw, 5), 3);
+        assert_eq!(line_of(raw, 7), 4);
+    }
+
+    #[test]
+    fn test_lint_cases() {
+        let issues = lint(
+            &[(
+                "game/001-a.txt",
+                b"# @babblewitz:games: eu4\n\
+                # @babblewitz:case: claimed\n\
+                a=b\n\
+                # @babblewitz:case: unclaimed\n\
+                # @babblewitz:games: hoi4\n\
+                a=b\n\
+                # @babblewitz:case: empty\n\
+                # @babblewitz:since: ck3@1.9\n\
+                \n\
+                # @babblewitz:case: unicode\n\
+                a=b\n\
+                name=\"M\xfcnchen\"\n",
+            )],
+            &[Game::Eu4],
+        );
+        assert_eq!(
+            issues,
+            vec![
+                issue(
+                    "game/001-a.txt",
+                    Some(4),
+                    Severity::Warning,
+                    "No implementation claims: hoi4"
+                ),
+                issue(
+                    "game/001-a.txt",
+                    Some(7),
+                    Severity::Warning,
+                    "Version constraint for unlisted game: ck3"
+                ),
+                issue(
+                    "game/001-a.txt",
+                    Some(9),
+                    Severity::Error,
+                    "Content is empty after stripping directives"
+                ),
+                issue(
+                    "game/001-a.txt",
+                    Some(12),
+                    Severity::Error,
+                    "Non-ASCII content without an encoding directive"
+                ),
+            ]
+        );
+    }
+}
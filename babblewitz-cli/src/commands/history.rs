@@ -0,0 +1,705 @@
+use crate::commands::tasks::can_parse::{CanParseFileResult, ResultsTable};
+use crate::commands::tasks::deserialization::{Clock, FileResult, PerformanceTable};
+use crate::core::config::TaskType;
+use crate::core::executor::Outcome;
+use crate::core::savefile::Game;
+use crate::core::stats;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Where the history of every run is kept, relative to the repository root
+pub fn default_path() -> PathBuf {
+    PathBuf::from("target")
+        .join("babblewitz")
+        .join("history.jsonl")
+}
+
+/// What a run was of: when it ran, the babblewitz revision, and the machine
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RunKey {
+    pub timestamp: DateTime<Utc>,
+    /// Git revision of the repository, suffixed with `-dirty` when it has
+    /// uncommitted changes
+    pub revision: Option<String>,
+    /// Hash identifying the machine's host name, CPU, and operating system
+    pub machine: String,
+}
+
+impl RunKey {
+    /// Describe the run happening now
+    pub fn now() -> RunKey {
+        RunKey {
+            timestamp: Utc::now(),
+            revision: git_revision(),
+            machine: machine_fingerprint(),
+        }
+    }
+}
+
+/// A result recorded in the history
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HistoryEntry {
+    #[serde(flatten)]
+    pub run: RunKey,
+    pub task: TaskType,
+    pub implementation: String,
+    /// Hash of the implementation's source files
+    pub source_hash: Option<String>,
+    pub record: Record,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Record {
+    /// Measurements of a save file
+    Benchmark(FileResult),
+    /// Conformance of an implementation for a game
+    Conformance(CanParseFileResult),
+    /// A corpus file an implementation failed
+    ConformanceFailure {
+        corpus_file: String,
+        /// Games the corpus file failed for
+        #[serde(default)]
+        games: Vec<Game>,
+    },
+}
+
+/// Append the results of a deserialization run to the history. Sources are
+/// found in `implementation_dir` when given, else under `impls/`.
+pub fn record_benchmarks(
+    path: &Path,
+    table: &PerformanceTable,
+    implementation_dir: Option<&Path>,
+) -> Result<()> {
+    let run = RunKey::now();
+    let source_hashes = SourceHashes::new(implementation_dir);
+    let entries: Vec<HistoryEntry> = table
+        .files
+        .iter()
+        .map(|file| HistoryEntry {
+            run: run.clone(),
+            task: TaskType::Deserialization,
+            implementation: file.implementation.clone(),
+            source_hash: source_hashes.get(&file.implementation),
+            record: Record::Benchmark(file.clone()),
+        })
+        .collect();
+    append(path, &entries)
+}
+
+/// Append the results of a conformance run to the history. Sources are
+/// found in `implementation_dir` when given, else under `impls/`.
+pub fn record_conformance(
+    path: &Path,
+    task: TaskType,
+    table: &ResultsTable,
+    implementation_dir: Option<&Path>,
+) -> Result<()> {
+    let run = RunKey::now();
+    let source_hashes = SourceHashes::new(implementation_dir);
+    let entry = |implementation: &str, record: Record| HistoryEntry {
+        run: run.clone(),
+        task,
+        implementation: implementation.to_string(),
+        source_hash: source_hashes.get(implementation),
+        record,
+    };
+
+    let results = table
+        .results
+        .iter()
+        .map(|x| entry(&x.implementation, Record::Conformance(x.clone())));
+    let failures = table.failures.iter().map(|x| {
+        entry(
+            &x.implementation,
+            Record::ConformanceFailure {
+                corpus_file: x.corpus_file.clone(),
+                games: x.games.clone(),
+            },
+        )
+    });
+    append(path, &results.chain(failures).collect::<Vec<_>>())
+}
+
+/// Append entries to the history file, creating it if needed
+pub fn append(path: &Path, entries: &[HistoryEntry]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let mut lines = Vec::new();
+    for entry in entries {
+        serde_json::to_writer(&mut lines, entry)?;
+        lines.push(b'\n');
+    }
+
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(&lines))
+        .with_context(|| format!("Failed to write history to {}", path.display()))
+}
+
+/// Read every entry in the history file, which is empty if it doesn't exist
+pub fn load(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("Failed to read history from {}", path.display()))
+        }
+    };
+
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).with_context(|| {
+                format!("Failed to parse line {} of {}", index + 1, path.display())
+            })
+        })
+        .collect()
+}
+
+/// Source hashes of implementations, computed once per implementation
+struct SourceHashes {
+    implementation_dir: Option<PathBuf>,
+    hashes: std::cell::RefCell<BTreeMap<String, Option<String>>>,
+}
+
+impl SourceHashes {
+    fn new(implementation_dir: Option<&Path>) -> Self {
+        Self {
+            implementation_dir: implementation_dir.map(Path::to_path_buf),
+            hashes: Default::default(),
+        }
+    }
+
+    fn get(&self, implementation: &str) -> Option<String> {
+        self.hashes
+            .borrow_mut()
+            .entry(implementation.to_string())
+            .or_insert_with(|| {
+                let dir = match &self.implementation_dir {
+                    Some(dir) => dir.clone(),
+                    None => PathBuf::from("impls").join(implementation),
+                };
+                source_hash(&dir)
+            })
+            .clone()
+    }
+}
+
+/// Hash the files git would track in the implementation directory, so build
+/// output and other ignored files don't change the hash
+fn source_hash(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ])
+        .output()
+        .ok()
+        .filter(|x| x.status.success())?;
+
+    let mut files: Vec<&[u8]> = output
+        .stdout
+        .split(|x| *x == 0)
+        .filter(|x| !x.is_empty())
+        .collect();
+    files.sort();
+
+    let mut hash = Fnv1a::default();
+    for file in files {
+        // Files deleted from the working tree are still listed as tracked
+        let Ok(content) = std::fs::read(dir.join(String::from_utf8_lossy(file).as_ref())) else {
+            continue;
+        };
+        hash.write(file);
+        hash.write(&(content.len() as u64).to_le_bytes());
+        hash.write(&content);
+    }
+    Some(hash.hex())
+}
+
+fn git_revision() -> Option<String> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|x| x.status.success())
+            .map(|x| String::from_utf8_lossy(&x.stdout).trim().to_string())
+    };
+
+    let revision = git(&["rev-parse", "--short=12", "HEAD"])?;
+    match git(&["status", "--porcelain", "--untracked-files=no"]) {
+        Some(status) if !status.is_empty() => Some(format!("{}-dirty", revision)),
+        _ => Some(revision),
+    }
+}
+
+/// Hash of what distinguishes this machine's benchmark results from another's
+fn machine_fingerprint() -> String {
+    let read = |path: &str| std::fs::read_to_string(path).unwrap_or_default();
+    let hostname = read("/proc/sys/kernel/hostname");
+    let cpuinfo = read("/proc/cpuinfo");
+    let cpu_model = cpuinfo
+        .lines()
+        .find(|x| x.starts_with("model name"))
+        .unwrap_or_default();
+    let cpus = std::thread::available_parallelism().map_or(0, |x| x.get());
+
+    let mut hash = Fnv1a::default();
+    for part in [
+        hostname.trim(),
+        cpu_model,
+        &cpus.to_string(),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    ] {
+        hash.write(part.as_bytes());
+        hash.write(&[0]);
+    }
+    hash.hex()
+}
+
+/// 64-bit FNV-1a hash, which unlike the standard library's hasher is stable
+/// across Rust versions
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn hex(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+/// Which entries of the history to show
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub implementation: Option<String>,
+    pub game: Option<Game>,
+    /// Include runs from other machines, whose throughput isn't comparable
+    pub all_machines: bool,
+    /// Most recent runs to show of each implementation and game
+    pub limit: usize,
+}
+
+/// How an implementation's results for a game changed over its runs
+#[derive(Debug)]
+pub struct Trend {
+    pub task: TaskType,
+    pub implementation: String,
+    pub game: Game,
+    pub points: Vec<TrendPoint>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TrendPoint {
+    pub timestamp: DateTime<Utc>,
+    pub revision: Option<String>,
+    pub source_hash: Option<String>,
+    /// Median throughput in MB/s for benchmarks, or the success rate in
+    /// percent for conformance. `None` when no benchmark run succeeded.
+    pub value: Option<f64>,
+}
+
+/// A file that failed for a game in a run after passing in the run before
+#[derive(Debug, PartialEq)]
+pub struct Regression {
+    pub timestamp: DateTime<Utc>,
+    pub task: TaskType,
+    pub implementation: String,
+    pub game: Game,
+    pub file: String,
+}
+
+/// The files of each game that a run ran, and those that failed
+#[derive(Default)]
+struct RunFiles {
+    run: BTreeSet<(Game, String)>,
+    failed: BTreeSet<(Game, String)>,
+}
+
+/// Samples of a trend point as they are collected
+#[derive(Default)]
+struct PointSamples {
+    revision: Option<String>,
+    source_hash: Option<String>,
+    throughputs: Vec<f64>,
+    success_rate: Option<f64>,
+}
+
+/// Follow the self-reported throughput and conformance of each
+/// implementation and game across runs, oldest first
+pub fn trends(entries: &[HistoryEntry]) -> Vec<Trend> {
+    type TrendKey = (&'static str, String, Game);
+    let mut trends: BTreeMap<TrendKey, (TaskType, BTreeMap<DateTime<Utc>, PointSamples>)> =
+        BTreeMap::new();
+
+    for entry in entries {
+        let (game, throughputs, success_rate) = match &entry.record {
+            Record::Benchmark(file) => (file.game, file.throughputs(Clock::SelfReported), None),
+            Record::Conformance(result) => (result.game, Vec::new(), Some(result.success_rate)),
+            Record::ConformanceFailure { .. } => continue,
+        };
+
+        let key = (entry.task.as_str(), entry.implementation.clone(), game);
+        let (_, points) = trends
+            .entry(key)
+            .or_insert_with(|| (entry.task, BTreeMap::new()));
+        let point = points.entry(entry.run.timestamp).or_default();
+        point.revision.clone_from(&entry.run.revision);
+        point.source_hash.clone_from(&entry.source_hash);
+        point.throughputs.extend(throughputs);
+        point.success_rate = success_rate.or(point.success_rate);
+    }
+
+    trends
+        .into_iter()
+        .map(|((_, implementation, game), (task, points))| Trend {
+            task,
+            implementation,
+            game,
+            points: points
+                .into_iter()
+                .map(|(timestamp, samples)| TrendPoint {
+                    timestamp,
+                    revision: samples.revision,
+                    source_hash: samples.source_hash,
+                    value: samples
+                        .success_rate
+                        .or_else(|| stats::percentile(&samples.throughputs, 50.0)),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Files that started failing in each run of an implementation's task.
+/// Files the run before didn't run, like newly added ones, aren't included.
+pub fn regressions(entries: &[HistoryEntry]) -> Vec<Regression> {
+    type Runs = BTreeMap<DateTime<Utc>, RunFiles>;
+    let mut tasks: BTreeMap<(&'static str, String), (TaskType, Runs)> = BTreeMap::new();
+
+    for entry in entries {
+        let (_, runs) = tasks
+            .entry((entry.task.as_str(), entry.implementation.clone()))
+            .or_insert_with(|| (entry.task, BTreeMap::new()));
+        let files = runs.entry(entry.run.timestamp).or_default();
+        match &entry.record {
+            Record::Benchmark(file) => {
+                let key = (file.game, file.file_name.clone());
+                if file.result.outcome() != Outcome::Success {
+                    files.failed.insert(key.clone());
+                }
+                files.run.insert(key);
+            }
+            Record::Conformance(result) => files.run.extend(
+                result
+                    .corpus_files
+                    .iter()
+                    .map(|file| (result.game, file.clone())),
+            ),
+            Record::ConformanceFailure { corpus_file, games } => files
+                .failed
+                .extend(games.iter().map(|game| (*game, corpus_file.clone()))),
+        }
+    }
+
+    let mut result = Vec::new();
+    for ((_, implementation), (task, runs)) in tasks {
+        let mut previous: Option<RunFiles> = None;
+        for (timestamp, files) in runs {
+            if let Some(previous) = &previous {
+                let passed = previous
+                    .run
+                    .difference(&previous.failed)
+                    .collect::<BTreeSet<_>>();
+                for (game, file) in files.failed.iter().filter(|x| passed.contains(x)) {
+                    result.push(Regression {
+                        timestamp,
+                        task,
+                        implementation: implementation.clone(),
+                        game: *game,
+                        file: file.clone(),
+                    });
+                }
+            }
+            previous = Some(files);
+        }
+    }
+    result.sort_by_key(|x| x.timestamp);
+    result
+}
+
+/// Print the trends and regressions in the history that match the filter
+pub fn print_history(path: &Path, filter: &HistoryFilter) -> Result<()> {
+    let machine = machine_fingerprint();
+    let entries: Vec<HistoryEntry> = load(path)?
+        .into_iter()
+        .filter(|x| filter.all_machines || x.run.machine == machine)
+        .filter(|x| {
+            filter
+                .implementation
+                .as_ref()
+                .is_none_or(|name| &x.implementation == name)
+        })
+        .collect();
+
+    let runs: BTreeSet<DateTime<Utc>> = entries.iter().map(|x| x.run.timestamp).collect();
+    println!(
+        "{} runs recorded in {}{}",
+        runs.len(),
+        path.display(),
+        if filter.all_machines {
+            ""
+        } else {
+            " on this machine"
+        }
+    );
+
+    for trend in trends(&entries) {
+        if filter.game.is_some_and(|game| game != trend.game) {
+            continue;
+        }
+
+        println!();
+        match trend.task {
+            TaskType::Deserialization => println!(
+                "{} {} (median self-reported throughput)",
+                trend.implementation, trend.game
+            ),
+            _ => println!(
+                "{} {} ({} success rate)",
+                trend.implementation, trend.game, trend.task
+            ),
+        }
+
+        let skip = trend.points.len().saturating_sub(filter.limit);
+        let mut previous = skip.checked_sub(1).and_then(|x| trend.points[x].value);
+        for point in &trend.points[skip..] {
+            let (value, change) = match (trend.task, point.value) {
+                (_, None) => (String::from("failed"), String::new()),
+                (TaskType::Deserialization, Some(value)) => (
+                    format!("{:.1} MB/s", value),
+                    previous.map_or_else(String::new, |x| {
+                        format!("{:+.1}%", (value / x - 1.0) * 100.0)
+                    }),
+                ),
+                (_, Some(value)) => (
+                    format!("{:.1}%", value),
+                    previous.map_or_else(String::new, |x| format!("{:+.1} pts", value - x)),
+                ),
+            };
+            println!(
+                "  {}  {:<18} source {:<16} {:>12} {:>10}",
+                point.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                point.revision.as_deref().unwrap_or("-"),
+                point.source_hash.as_deref().unwrap_or("-"),
+                value,
+                change
+            );
+            previous = point.value.or(previous);
+        }
+    }
+
+    let regressions: Vec<Regression> = regressions(&entries)
+        .into_iter()
+        .filter(|x| filter.game.is_none_or(|game| game == x.game))
+        .collect();
+    let shown = regressions.len().saturating_sub(filter.limit);
+    if regressions.len() > shown {
+        println!();
+        println!("Files that started failing:");
+        for regression in &regressions[shown..] {
+            println!(
+                "  {}  {} {} {}: {}",
+                regression.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                regression.implementation,
+                regression.task,
+                regression.game,
+                regression.file
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tasks::deserialization::{FileTestResult, RunMeasurement, Timings};
+    use std::time::Duration;
+
+    fn run(seconds: i64) -> RunKey {
+        RunKey {
+            timestamp: DateTime::from_timestamp(seconds, 0).unwrap(),
+            revision: Some(String::from("abc")),
+            machine: String::from("machine"),
+        }
+    }
+
+    fn benchmark(run: RunKey, file_name: &str, millis: Option<u64>) -> HistoryEntry {
+        let result = match millis {
+            Some(millis) => FileTestResult::Success {
+                runs: vec![RunMeasurement {
+                    timings: Timings {
+                        self_reported: Duration::from_millis(millis),
+                        wall: Duration::from_millis(millis),
+                        cpu: None,
+                    },
+                    usage: None,
+                    phases: Vec::new(),
+                    warnings: Vec::new(),
+                }],
+            },
+            None => FileTestResult::Failed {
+                outcome: Outcome::Crashed,
+                error: String::from("Crashed"),
+            },
+        };
+        HistoryEntry {
+            run,
+            task: TaskType::Deserialization,
+            implementation: String::from("jomini-tape"),
+            source_hash: None,
+            record: Record::Benchmark(FileResult {
+                game: Game::Eu4,
+                implementation: String::from("jomini-tape"),
+                file_name: file_name.to_string(),
+                data_size_bytes: 1024 * 1024,
+                result,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("babblewitz").join("history.jsonl");
+        assert!(load(&path).unwrap().is_empty());
+
+        append(&path, &[benchmark(run(1), "a.eu4", Some(100))]).unwrap();
+        let failure = HistoryEntry {
+            run: run(2),
+            task: TaskType::CanParse,
+            implementation: String::from("jomini-tape"),
+            source_hash: Some(String::from("0123")),
+            record: Record::ConformanceFailure {
+                corpus_file: String::from("strings.txt"),
+                games: vec![Game::Eu4],
+            },
+        };
+        append(&path, &[failure]).unwrap();
+
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].run, run(1));
+        assert!(matches!(
+            &entries[1].record,
+            Record::ConformanceFailure { corpus_file, games }
+                if corpus_file == "strings.txt" && games == &[Game::Eu4]
+        ));
+    }
+
+    #[test]
+    fn test_trends_and_regressions() {
+        let entries = [
+            benchmark(run(1), "a.eu4", Some(100)),
+            benchmark(run(1), "b.eu4", Some(100)),
+            benchmark(run(2), "a.eu4", Some(200)),
+            benchmark(run(2), "b.eu4", None),
+            // Added since the run before, so it can't have started failing
+            benchmark(run(2), "c.eu4", None),
+        ];
+
+        let trends = trends(&entries);
+        assert_eq!(trends.len(), 1);
+        assert_eq!(trends[0].game, Game::Eu4);
+        let values: Vec<_> = trends[0].points.iter().map(|x| x.value).collect();
+        assert_eq!(values, [Some(10.0), Some(5.0)]);
+
+        let regressions = regressions(&entries);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].file, "b.eu4");
+        assert_eq!(regressions[0].timestamp, run(2).timestamp);
+    }
+
+    #[test]
+    fn test_conformance_regressions() {
+        let conformance = |run: RunKey, game: Game, corpus_files: &[&str]| HistoryEntry {
+            run,
+            task: TaskType::CanParse,
+            implementation: String::from("jomini-tape"),
+            source_hash: None,
+            record: Record::Conformance(CanParseFileResult {
+                implementation: String::from("jomini-tape"),
+                game,
+                success_rate: 100.0,
+                lenient_success_rate: 100.0,
+                timed_out_tests: 0,
+                corpus_files: corpus_files.iter().map(|x| x.to_string()).collect(),
+            }),
+        };
+        let failure = |run: RunKey, corpus_file: &str, games: &[Game]| HistoryEntry {
+            run,
+            task: TaskType::CanParse,
+            implementation: String::from("jomini-tape"),
+            source_hash: None,
+            record: Record::ConformanceFailure {
+                corpus_file: corpus_file.to_string(),
+                games: games.to_vec(),
+            },
+        };
+
+        let entries = [
+            conformance(run(1), Game::Eu4, &["a.txt", "b.txt"]),
+            conformance(run(1), Game::Ck3, &["a.txt", "b.txt"]),
+            failure(run(1), "b.txt", &[Game::Eu4]),
+            conformance(run(2), Game::Eu4, &["a.txt", "b.txt", "new.txt"]),
+            conformance(run(2), Game::Ck3, &["a.txt", "b.txt", "new.txt"]),
+            failure(run(2), "b.txt", &[Game::Eu4, Game::Ck3]),
+            failure(run(2), "new.txt", &[Game::Eu4]),
+        ];
+
+        let regressions = regressions(&entries);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].game, Game::Ck3);
+        assert_eq!(regressions[0].file, "b.txt");
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        let mut hash = Fnv1a::default();
+        hash.write(b"a");
+        assert_eq!(hash.hex(), "af63dc4c8601ec8c");
+        assert_eq!(machine_fingerprint(), machine_fingerprint());
+    }
+}
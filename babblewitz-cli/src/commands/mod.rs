@@ -1,3 +1,7 @@
+pub mod baseline;
 pub mod build;
+pub mod compare;
+pub mod corpus;
+pub mod history;
 pub mod sync_assets;
 pub mod tasks;
@@ -1,98 +1,233 @@
-use crate::core::common::{calculate_impl_width, print_table_header};
+use crate::core::common::{calculate_label_width, print_labeled_table_header, print_outcome_table};
 use crate::core::config::TaskType;
-use crate::core::corpus;
+use crate::core::corpus::{self, Expect};
 use crate::core::executor::ExecutionResult;
-use crate::core::executor::ImplementationExecutor;
+use crate::core::executor::{ImplementationExecutor, OutcomeCounts};
 use crate::core::implementation::Implementation;
+use crate::core::jobs;
+use crate::core::results;
 use crate::core::savefile::Game;
 use anyhow::Result;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-#[derive(Debug, serde::Serialize)]
-pub struct CanParseGameResult {
-    pub game: Game,
+/// Pass counts for a group of can-parse tests
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct CanParseTally {
     pub total_tests: usize,
+    /// Tests where the outcome matched the corpus expectation exactly
     pub passed_tests: usize,
+    /// Tests that passed when accepting malformed input is tolerated
+    pub lenient_passed_tests: usize,
+    /// Tests killed for exceeding the timeout
+    pub timed_out_tests: usize,
 }
 
-impl CanParseGameResult {
-    pub fn new(game: Game) -> Self {
-        Self {
-            game,
-            total_tests: 0,
-            passed_tests: 0,
-        }
+impl CanParseTally {
+    fn record(&mut self, strict: bool, lenient: bool) {
+        self.total_tests += 1;
+        self.passed_tests += usize::from(strict);
+        self.lenient_passed_tests += usize::from(lenient);
     }
 
     pub fn success_rate(&self) -> f64 {
+        self.rate(self.passed_tests)
+    }
+
+    pub fn lenient_success_rate(&self) -> f64 {
+        self.rate(self.lenient_passed_tests)
+    }
+
+    fn rate(&self, passed: usize) -> f64 {
         if self.total_tests > 0 {
-            (self.passed_tests as f64 / self.total_tests as f64) * 100.0
+            (passed as f64 / self.total_tests as f64) * 100.0
         } else {
             0.0
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, serde::Serialize)]
+pub struct CanParseGameResult {
+    pub game: Game,
+    #[serde(flatten)]
+    pub tally: CanParseTally,
+    /// Corpus files and cases run for the game, in corpus order
+    pub corpus_files: Vec<String>,
+}
+
+/// Results for a syntax feature, tallied once per test case carrying the
+/// feature's tag
+#[derive(Debug, serde::Serialize)]
+pub struct CanParseFeatureResult {
+    pub feature: String,
+    #[serde(flatten)]
+    pub tally: CanParseTally,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CanParseFileResult {
     pub implementation: String,
     pub game: Game,
     pub success_rate: f64,
+    pub lenient_success_rate: f64,
+    pub timed_out_tests: usize,
+    /// Corpus files and cases run for the game, so that files added or
+    /// removed since another run aren't mistaken for regressions
+    #[serde(default)]
+    pub corpus_files: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct CanParseFeatureRates {
+    pub implementation: String,
+    pub feature: String,
+    pub success_rate: f64,
+    pub lenient_success_rate: f64,
+    pub timed_out_tests: usize,
+}
+
+/// Feature reported for corpus files without any tags
+const UNTAGGED_FEATURE: &str = "untagged";
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FailureDetail {
     pub implementation: String,
     pub corpus_file: String,
+    /// Games the corpus file failed for
+    pub games: Vec<Game>,
     pub error_message: String,
+    /// Description and issue links from the corpus file header
+    pub notes: Vec<String>,
+}
+
+/// A corpus file left out for some games because it requires a newer game
+/// version than the implementation supports
+#[derive(Debug)]
+pub struct SkippedDetail {
+    pub implementation: String,
+    pub corpus_file: String,
+    pub reason: String,
+}
+
+/// A warning an implementation reported while handling a corpus file
+#[derive(Debug)]
+pub struct WarningDetail {
+    pub implementation: String,
+    pub corpus_file: String,
+    pub message: String,
 }
 
 #[derive(Debug)]
 pub struct ResultsTable {
     pub results: Vec<CanParseFileResult>,
+    pub feature_results: Vec<CanParseFeatureRates>,
     pub implementations: Vec<String>,
     pub games: Vec<Game>,
+    pub features: Vec<String>,
     pub failures: Vec<FailureDetail>,
+    pub skipped: Vec<SkippedDetail>,
+    pub warnings: Vec<WarningDetail>,
+    /// How each implementation's executions turned out
+    pub outcomes: Vec<(String, OutcomeCounts)>,
 }
 
-pub fn run_can_parse_tests(implementation_path: &Path) -> Result<ResultsTable> {
+/// The results of a conformance run as saved to a results file
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ConformanceResults {
+    pub results: Vec<CanParseFileResult>,
+    pub failures: Vec<FailureDetail>,
+}
+
+impl ConformanceResults {
+    /// Read a results file written by [`ResultsTable::save`] for the task
+    pub fn load(path: &Path, task: TaskType) -> Result<ConformanceResults> {
+        results::load(path, &[task])
+    }
+}
+
+/// Run a conformance task (can-parse, localisation) against a single
+/// implementation, executing up to `jobs` corpus files at once
+pub fn run_conformance_tests(
+    implementation_path: &Path,
+    task: TaskType,
+    jobs: usize,
+) -> Result<ResultsTable> {
     let implementation = Implementation::load_from_path(implementation_path)?;
 
-    let mut all_results = Vec::new();
-    let mut all_failures = Vec::new();
+    let mut table = ResultsTable::new(vec![implementation.name.clone()]);
+    process_implementation_can_parse(&implementation, task, jobs, &mut table)?;
+    table.collect_columns();
+    Ok(table)
+}
 
-    process_implementation_can_parse(&implementation, &mut all_results, &mut all_failures)?;
+impl ResultsTable {
+    /// Write the success rates, run corpus files, and failed corpus files of
+    /// the task to a JSON results file
+    pub fn save(&self, path: &Path, task: TaskType) -> Result<()> {
+        let results = ConformanceResults {
+            results: self.results.clone(),
+            failures: self.failures.clone(),
+        };
+        results::save(path, task, &results)
+    }
 
-    // Derive games from results
-    let mut games: Vec<Game> = all_results
-        .iter()
-        .map(|r| r.game)
-        .collect::<HashSet<_>>()
-        .into_iter()
-        .collect();
-    games.sort();
+    pub fn new(implementations: Vec<String>) -> Self {
+        Self {
+            results: Vec::new(),
+            feature_results: Vec::new(),
+            implementations,
+            games: Vec::new(),
+            features: Vec::new(),
+            failures: Vec::new(),
+            skipped: Vec::new(),
+            warnings: Vec::new(),
+            outcomes: Vec::new(),
+        }
+    }
+
+    /// Derive the tested games and features from the results
+    fn collect_columns(&mut self) {
+        let mut games: Vec<Game> = self
+            .results
+            .iter()
+            .map(|r| r.game)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        games.sort();
+        self.games = games;
 
-    Ok(ResultsTable {
-        results: all_results,
-        implementations: vec![implementation.name.clone()],
-        games,
-        failures: all_failures,
-    })
+        let mut features: Vec<String> = self
+            .feature_results
+            .iter()
+            .map(|r| r.feature.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        features.sort();
+        self.features = features;
+    }
 }
 
 fn run_can_parase_tests_with_implementation(
     implementation: &Implementation,
+    task: TaskType,
+    jobs: usize,
     failures: &mut Vec<FailureDetail>,
-) -> Result<Vec<CanParseGameResult>> {
-    let games_to_test = implementation.games_for_task(TaskType::CanParse);
-    let all_corpus_files = corpus::collect_relevant_corpus_files(&games_to_test)?;
+    skipped: &mut Vec<SkippedDetail>,
+    warnings: &mut Vec<WarningDetail>,
+    outcome_counts: &mut OutcomeCounts,
+) -> Result<(Vec<CanParseGameResult>, Vec<CanParseFeatureResult>)> {
+    let games_to_test = implementation.games_for_task(task);
+    let all_corpus_files = corpus::collect_relevant_corpus_files(task, &games_to_test)?;
 
     let mut game_results = games_to_test
         .iter()
         .copied()
-        .map(|game| (game, CanParseGameResult::new(game)))
+        .map(|game| (game, (CanParseTally::default(), Vec::new())))
         .collect::<HashMap<_, _>>();
+    let mut feature_results: HashMap<String, CanParseTally> = HashMap::new();
 
     let executor = match ImplementationExecutor::build_implementation(implementation) {
         Ok(executor) => executor,
@@ -100,12 +235,15 @@ fn run_can_parase_tests_with_implementation(
             failures.push(FailureDetail {
                 implementation: implementation.name.clone(),
                 corpus_file: String::from("build"),
+                games: games_to_test.clone(),
                 error_message: e.to_string(),
+                notes: Vec::new(),
             });
-            return Ok(Vec::new());
+            return Ok((Vec::new(), Vec::new()));
         }
     };
 
+    let mut runs = Vec::new();
     for corpus_file in all_corpus_files {
         // Find which of our target games this file applies to
         let applicable_games: Vec<Game> = games_to_test
@@ -118,128 +256,297 @@ fn run_can_parase_tests_with_implementation(
             continue;
         }
 
-        for game in &applicable_games {
-            game_results.get_mut(game).unwrap().total_tests += 1;
+        let corpus_file_name = corpus_file.name();
+
+        // Inputs requiring a newer patch than supported aren't held against the implementation
+        let (applicable_games, too_new): (Vec<Game>, Vec<Game>) =
+            applicable_games.into_iter().partition(|game| {
+                corpus_file.since.for_game(*game).is_none_or(|since| {
+                    implementation
+                        .config
+                        .supports_version_for_task(task, *game, since)
+                })
+            });
+
+        if !too_new.is_empty() {
+            let requirements: Vec<String> = too_new
+                .iter()
+                .map(|game| {
+                    let since = corpus_file.since.for_game(*game).unwrap();
+                    match implementation
+                        .config
+                        .supported_versions_for_task(task, *game)
+                    {
+                        Some(range) => format!("{}@{} (supports {})", game, since, range),
+                        None => format!("{}@{}", game, since),
+                    }
+                })
+                .collect();
+            skipped.push(SkippedDetail {
+                implementation: implementation.name.clone(),
+                corpus_file: corpus_file_name.clone(),
+                reason: format!("Requires {}", requirements.join(", ")),
+            });
+        }
+
+        if applicable_games.is_empty() {
+            continue;
         }
 
-        let corpus_file_name = corpus_file
-            .path
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
+        runs.push((corpus_file, applicable_games));
+    }
+
+    // Execute on the worker pool, then score in corpus order so results and
+    // failures are the same regardless of the number of jobs
+    let outcomes = jobs::parallel_map(&runs, jobs, |(corpus_file, applicable_games)| {
+        executor.execute(
+            &corpus_file.content,
+            task,
+            applicable_games,
+            corpus_file.encoding,
+        )
+    });
+
+    for ((corpus_file, applicable_games), outcome) in runs.into_iter().zip(outcomes) {
+        let corpus_file_name = corpus_file.name();
+        let notes: Vec<String> = corpus_file
+            .metadata
+            .description()
+            .into_iter()
+            .chain(corpus_file.metadata.issues().iter().map(String::as_str))
+            .map(String::from)
+            .collect();
 
-        let mut add_failure = |error_msg: String| {
+        let mut add_failure = |games: &[Game], error_msg: String| {
             failures.push(FailureDetail {
                 implementation: implementation.name.clone(),
                 corpus_file: corpus_file_name.clone(),
+                games: games.to_vec(),
                 error_message: error_msg,
+                notes: notes.clone(),
             });
         };
 
-        match executor.execute(&corpus_file.content, TaskType::CanParse, &applicable_games) {
-            Ok(ExecutionResult::Success { .. }) => {
-                for game in applicable_games {
-                    game_results.get_mut(&game).unwrap().passed_tests += 1;
+        if let Ok(result) = &outcome {
+            outcome_counts.record(result.outcome());
+        }
+
+        // An implementation rejects an input by reporting it as rejected. Runs
+        // that fail for other reasons are neither accepted nor rejected.
+        let mut timed_out = false;
+        let accepted = match outcome {
+            Ok(ExecutionResult::Success {
+                output,
+                stderr,
+                warnings: reported,
+                ..
+            }) => {
+                warnings.extend(reported.into_iter().map(|message| WarningDetail {
+                    implementation: implementation.name.clone(),
+                    corpus_file: corpus_file_name.clone(),
+                    message,
+                }));
+                match corpus_file.expected.check(task, &output) {
+                    Ok(()) => Some(true),
+                    // Diagnostics may explain a wrong result
+                    Err(mismatch) if !stderr.trim().is_empty() => {
+                        add_failure(
+                            &applicable_games,
+                            format!("{}\nstderr: {}", mismatch, stderr.trim()),
+                        );
+                        None
+                    }
+                    Err(mismatch) => {
+                        add_failure(&applicable_games, mismatch);
+                        None
+                    }
+                }
+            }
+            Ok(result @ ExecutionResult::Rejected { .. }) => {
+                // Only report the rejection for games that expected acceptance
+                let expected_accept: Vec<Game> = applicable_games
+                    .iter()
+                    .copied()
+                    .filter(|game| corpus_file.expect.for_game(*game) == Expect::Accept)
+                    .collect();
+                if !expected_accept.is_empty() {
+                    add_failure(&expected_accept, result.error_message().unwrap_or_default());
                 }
+                Some(false)
+            }
+            Ok(result) => {
+                timed_out = matches!(result, ExecutionResult::Timeout { .. });
+                add_failure(
+                    &applicable_games,
+                    result.error_message().unwrap_or_default(),
+                );
+                None
+            }
+            Err(error) => {
+                add_failure(&applicable_games, error.to_string());
+                None
             }
-            Ok(ExecutionResult::Error { error }) => add_failure(error),
-            Err(error) => add_failure(error.to_string()),
+        };
+
+        // A case only passes for a feature if it passes for every game
+        let mut file_strict = true;
+        let mut file_lenient = true;
+        let mut wrongly_accepted = Vec::new();
+        for &game in &applicable_games {
+            let expect = corpus_file.expect.for_game(game);
+            let strict = accepted.is_some_and(|accepted| expect.strict_pass(accepted));
+            let lenient = accepted.is_some_and(|accepted| expect.lenient_pass(accepted));
+            if accepted == Some(true) && !strict {
+                wrongly_accepted.push(game);
+            }
+
+            let (tally, files) = game_results.get_mut(&game).unwrap();
+            tally.record(strict, lenient);
+            files.push(corpus_file_name.clone());
+            tally.timed_out_tests += usize::from(timed_out);
+            file_strict &= strict;
+            file_lenient &= lenient;
+        }
+
+        let tags = corpus_file.metadata.tags();
+        let features = if tags.is_empty() {
+            &[String::from(UNTAGGED_FEATURE)][..]
+        } else {
+            tags
+        };
+        for feature in features {
+            let tally = feature_results.entry(feature.clone()).or_default();
+            tally.record(file_strict, file_lenient);
+            tally.timed_out_tests += usize::from(timed_out);
+        }
+
+        if !wrongly_accepted.is_empty() {
+            let games: Vec<&str> = wrongly_accepted.iter().map(Game::as_str).collect();
+            add_failure(
+                &wrongly_accepted,
+                format!(
+                    "Accepted malformed input expected to be rejected for: {}",
+                    games.join(" ")
+                ),
+            );
         }
     }
 
-    let mut results: Vec<_> = game_results.into_values().collect();
+    let mut results: Vec<_> = game_results
+        .into_iter()
+        .map(|(game, (tally, corpus_files))| CanParseGameResult {
+            game,
+            tally,
+            corpus_files,
+        })
+        .collect();
     results.sort_by_key(|r| r.game);
-    Ok(results)
+
+    let mut features: Vec<_> = feature_results
+        .into_iter()
+        .map(|(feature, tally)| CanParseFeatureResult { feature, tally })
+        .collect();
+    features.sort_by(|a, b| a.feature.cmp(&b.feature));
+
+    Ok((results, features))
 }
 
-/// Process can parse tests for a single implementation
+/// Process conformance tests for a single implementation
 fn process_implementation_can_parse(
     implementation: &Implementation,
-    all_results: &mut Vec<CanParseFileResult>,
-    all_failures: &mut Vec<FailureDetail>,
+    task: TaskType,
+    jobs: usize,
+    table: &mut ResultsTable,
 ) -> Result<()> {
-    let results = run_can_parase_tests_with_implementation(implementation, all_failures)?;
+    let mut outcomes = OutcomeCounts::default();
+    let (results, features) = run_can_parase_tests_with_implementation(
+        implementation,
+        task,
+        jobs,
+        &mut table.failures,
+        &mut table.skipped,
+        &mut table.warnings,
+        &mut outcomes,
+    )?;
+    table.outcomes.push((implementation.name.clone(), outcomes));
 
     for result in results {
-        all_results.push(CanParseFileResult {
+        table.results.push(CanParseFileResult {
             implementation: implementation.name.clone(),
             game: result.game,
-            success_rate: result.success_rate(),
+            success_rate: result.tally.success_rate(),
+            lenient_success_rate: result.tally.lenient_success_rate(),
+            timed_out_tests: result.tally.timed_out_tests,
+            corpus_files: result.corpus_files,
+        });
+    }
+
+    for result in features {
+        table.feature_results.push(CanParseFeatureRates {
+            implementation: implementation.name.clone(),
+            feature: result.feature,
+            success_rate: result.tally.success_rate(),
+            lenient_success_rate: result.tally.lenient_success_rate(),
+            timed_out_tests: result.tally.timed_out_tests,
         });
     }
 
     Ok(())
 }
 
-/// Run can parse tests across all implementations and return table data
-pub fn run_all_can_parse() -> Result<ResultsTable> {
-    let implementations =
-        crate::core::implementation::find_implementations_for_task(TaskType::CanParse)?;
-
-    let mut all_results = Vec::new();
-    let mut all_failures = Vec::new();
-
-    // Process each implementation
-    for implementation in &implementations {
-        process_implementation_can_parse(implementation, &mut all_results, &mut all_failures)?;
+/// Run a conformance task across all implementations and return table data,
+/// executing up to `jobs` corpus files at once. A task no implementation
+/// supports yet has empty results.
+pub fn run_all_conformance(task: TaskType, jobs: usize) -> Result<ResultsTable> {
+    let implementations: Vec<Implementation> =
+        crate::core::implementation::find_all_implementations()?
+            .into_iter()
+            .filter(|x| x.supports_task(task))
+            .collect();
+    if implementations.is_empty() {
+        println!("No implementations support the {} task yet", task);
     }
 
-    // Pick out all the games we tested
-    let mut games: Vec<Game> = all_results
-        .iter()
-        .map(|r| r.game)
-        .collect::<HashSet<_>>()
-        .into_iter()
-        .collect();
-    games.sort();
-
     let implementation_names: Vec<String> = implementations
         .iter()
         .map(|impl_| impl_.name.clone())
         .collect();
+    let mut table = ResultsTable::new(implementation_names);
 
-    Ok(ResultsTable {
-        results: all_results,
-        implementations: implementation_names,
-        games,
-        failures: all_failures,
-    })
+    // Process each implementation
+    for implementation in &implementations {
+        process_implementation_can_parse(implementation, task, jobs, &mut table)?;
+    }
+
+    // Pick out all the games and features we tested
+    table.collect_columns();
+    Ok(table)
 }
 
 /// Print can parse results as a GitHub-friendly markdown summary
-pub fn print_github_summary(table: &ResultsTable) {
-    // Group results by implementation for display
-    let mut impl_results: HashMap<String, HashMap<Game, &CanParseFileResult>> = HashMap::new();
-    for result in &table.results {
-        impl_results
-            .entry(result.implementation.clone())
-            .or_default()
-            .insert(result.game, result);
-    }
+pub fn print_github_summary(table: &ResultsTable, pivot: Pivot) {
+    let matrix = table.matrix(pivot);
 
     println!(
-        "| Implementation | {} |",
-        table
-            .games
+        "| {} | {} |",
+        matrix.label,
+        matrix
+            .columns
             .iter()
-            .map(|g| g.to_string().to_uppercase())
+            .map(|c| c.to_uppercase())
             .collect::<Vec<_>>()
             .join(" | ")
     );
-    println!("|{}|", vec!["---"; table.games.len() + 1].join("|"));
-
-    for impl_name in &table.implementations {
-        print!("| **{}** |", impl_name);
+    println!("|{}|", vec!["---"; matrix.columns.len() + 1].join("|"));
 
-        let game_results = impl_results
-            .get(impl_name)
-            .expect("Implementation should have results");
+    for (row, row_name) in matrix.rows.iter().enumerate() {
+        print!("| **{}** |", row_name);
 
-        for game in &table.games {
+        for column in 0..matrix.columns.len() {
             // Not all implementations support all games
-            let display_value = match game_results.get(game) {
-                Some(result) if result.success_rate >= 100.0 => " ✅",
+            let display_value = match matrix.rate(row, column) {
+                Some(_) if matrix.timeouts(row, column) > 0 => " ⏱️",
+                Some(rate) if rate >= 100.0 => " ✅",
                 Some(_) => " ⚠️",
                 None => " ",
             };
@@ -270,38 +577,287 @@ pub fn print_failure_details(table: &ResultsTable) {
             "{} ({}): {}",
             failure.implementation, failure.corpus_file, failure.error_message
         );
+        for note in &failure.notes {
+            println!("    {}", note);
+        }
+    }
+}
+
+/// Print how many runs of each implementation succeeded, were rejected, or
+/// failed in each way
+pub fn print_outcome_details(table: &ResultsTable) {
+    print_outcome_table(&table.outcomes);
+}
+
+/// Print corpus files skipped due to game version constraints
+pub fn print_skipped_details(table: &ResultsTable) {
+    if table.skipped.is_empty() {
+        return;
+    }
+
+    println!("\nSkipped corpus files:");
+    for skipped in &table.skipped {
+        println!(
+            "{} ({}): {}",
+            skipped.implementation, skipped.corpus_file, skipped.reason
+        );
+    }
+}
+
+/// Print warnings implementations reported for corpus files
+pub fn print_warning_details(table: &ResultsTable) {
+    if table.warnings.is_empty() {
+        return;
+    }
+
+    println!("\nImplementation warnings:");
+    for warning in &table.warnings {
+        println!(
+            "{} ({}): {}",
+            warning.implementation, warning.corpus_file, warning.message
+        );
     }
 }
 
 /// Print can parse results as a table
-pub fn print_can_parse_table(table: &ResultsTable) {
-    let max_impl_width = calculate_impl_width(&table.implementations);
-    let game_col_width = 10;
+pub fn print_can_parse_table(table: &ResultsTable, pivot: Pivot) {
+    let matrix = table.matrix(pivot);
+    let row_width = calculate_label_width(matrix.label, &matrix.rows);
+    let col_width = matrix.columns.iter().map(|c| c.len()).fold(10, usize::max);
 
     // Print header
-    let game_strings: Vec<String> = table.games.iter().map(|g| g.to_string()).collect();
-    print_table_header(max_impl_width, &game_strings, game_col_width);
+    print_labeled_table_header(matrix.label, row_width, &matrix.columns, col_width);
 
     // Print data rows
-    for impl_name in &table.implementations {
-        print!("{:<width$} ", impl_name, width = max_impl_width);
-
-        let game_results = table
-            .results
-            .iter()
-            .filter(|r| r.implementation == *impl_name)
-            .map(|r| (r.game, r))
-            .collect::<HashMap<_, _>>();
+    for (row, row_name) in matrix.rows.iter().enumerate() {
+        print!("{:<width$} ", row_name, width = row_width);
 
-        for game in &table.games {
+        for column in 0..matrix.columns.len() {
             // Not all implementations support all games
-            let display_value = match game_results.get(game) {
-                Some(result) if result.success_rate >= 100.0 => String::from("✓"),
-                Some(result) => format!("{:.0}%", result.success_rate),
+            let mut display_value = match matrix.rate(row, column) {
+                Some(rate) if rate >= 100.0 => String::from("✓"),
+                Some(rate) => format!("{:.0}%", rate),
                 None => String::from(""),
             };
-            print!("{:>width$} ", display_value, width = game_col_width);
+            if matrix.timeouts(row, column) > 0 {
+                display_value.push_str(" ⏱");
+            }
+            print!("{:>width$} ", display_value, width = col_width);
         }
         println!();
     }
 }
+
+/// How results are grouped when displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Pivot {
+    /// Implementations as rows and games as columns
+    Game,
+    /// Syntax features as rows and implementations as columns
+    Feature,
+}
+
+impl Pivot {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Pivot::Game => "game",
+            Pivot::Feature => "feature",
+        }
+    }
+}
+
+impl std::fmt::Display for Pivot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// How malformed inputs are scored when displaying results
+#[derive(Debug, Clone, Copy)]
+enum Mode {
+    /// Malformed inputs must be rejected
+    Strict,
+    /// Accepting malformed inputs is tolerated
+    Lenient,
+}
+
+impl Mode {
+    const ALL: [Mode; 2] = [Mode::Strict, Mode::Lenient];
+
+    fn column(&self, name: &str) -> String {
+        match self {
+            Mode::Strict => format!("{} strict", name),
+            Mode::Lenient => format!("{} lenient", name),
+        }
+    }
+
+    fn select(&self, success_rate: f64, lenient_success_rate: f64) -> f64 {
+        match self {
+            Mode::Strict => success_rate,
+            Mode::Lenient => lenient_success_rate,
+        }
+    }
+}
+
+/// Success rates laid out as rows and columns for display, with a strict and
+/// lenient column for each group
+struct Matrix {
+    label: &'static str,
+    rows: Vec<String>,
+    columns: Vec<String>,
+    rates: HashMap<(usize, usize), f64>,
+    timeouts: HashMap<(usize, usize), usize>,
+}
+
+impl Matrix {
+    fn new(label: &'static str, rows: Vec<String>, groups: &[String]) -> Self {
+        let columns = groups
+            .iter()
+            .flat_map(|group| Mode::ALL.map(|mode| mode.column(group)))
+            .collect();
+
+        Matrix {
+            label,
+            rows,
+            columns,
+            rates: HashMap::new(),
+            timeouts: HashMap::new(),
+        }
+    }
+
+    fn insert(
+        &mut self,
+        row: usize,
+        group: usize,
+        success_rate: f64,
+        lenient_success_rate: f64,
+        timeouts: usize,
+    ) {
+        for (i, mode) in Mode::ALL.iter().enumerate() {
+            let column = group * Mode::ALL.len() + i;
+            let rate = mode.select(success_rate, lenient_success_rate);
+            self.rates.insert((row, column), rate);
+            self.timeouts.insert((row, column), timeouts);
+        }
+    }
+
+    fn rate(&self, row: usize, column: usize) -> Option<f64> {
+        self.rates.get(&(row, column)).copied()
+    }
+
+    /// Number of tests in a cell that timed out
+    fn timeouts(&self, row: usize, column: usize) -> usize {
+        self.timeouts.get(&(row, column)).copied().unwrap_or(0)
+    }
+}
+
+impl ResultsTable {
+    fn matrix(&self, pivot: Pivot) -> Matrix {
+        let position = |names: &[String], name: &str| names.iter().position(|x| x == name);
+
+        match pivot {
+            Pivot::Game => {
+                let games: Vec<String> = self.games.iter().map(|g| g.to_string()).collect();
+                let mut matrix =
+                    Matrix::new("Implementation", self.implementations.clone(), &games);
+                for result in &self.results {
+                    let row = position(&self.implementations, &result.implementation);
+                    let group = self.games.iter().position(|g| *g == result.game);
+                    if let (Some(row), Some(group)) = (row, group) {
+                        matrix.insert(
+                            row,
+                            group,
+                            result.success_rate,
+                            result.lenient_success_rate,
+                            result.timed_out_tests,
+                        );
+                    }
+                }
+                matrix
+            }
+            Pivot::Feature => {
+                let mut matrix =
+                    Matrix::new("Feature", self.features.clone(), &self.implementations);
+                for result in &self.feature_results {
+                    let row = position(&self.features, &result.feature);
+                    let group = position(&self.implementations, &result.implementation);
+                    if let (Some(row), Some(group)) = (row, group) {
+                        matrix.insert(
+                            row,
+                            group,
+                            result.success_rate,
+                            result.lenient_success_rate,
+                            result.timed_out_tests,
+                        );
+                    }
+                }
+                matrix
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_parse_tally() {
+        let mut tally = CanParseTally::default();
+        assert_eq!(tally.success_rate(), 0.0);
+
+        tally.record(true, true);
+        tally.record(false, true);
+        tally.record(false, false);
+        tally.record(true, true);
+
+        assert_eq!(tally.total_tests, 4);
+        assert_eq!(tally.success_rate(), 50.0);
+        assert_eq!(tally.lenient_success_rate(), 75.0);
+    }
+
+    #[test]
+    fn test_feature_matrix() {
+        let mut table = ResultsTable::new(vec![String::from("impl-a"), String::from("impl-b")]);
+        table.feature_results = vec![
+            CanParseFeatureRates {
+                implementation: String::from("impl-b"),
+                feature: String::from("operators"),
+                success_rate: 50.0,
+                lenient_success_rate: 100.0,
+                timed_out_tests: 1,
+            },
+            CanParseFeatureRates {
+                implementation: String::from("impl-a"),
+                feature: String::from("hsv"),
+                success_rate: 100.0,
+                lenient_success_rate: 100.0,
+                timed_out_tests: 0,
+            },
+        ];
+        table.collect_columns();
+
+        let matrix = table.matrix(Pivot::Feature);
+        assert_eq!(matrix.rows, vec!["hsv", "operators"]);
+        assert_eq!(
+            matrix.columns,
+            vec![
+                "impl-a strict",
+                "impl-a lenient",
+                "impl-b strict",
+                "impl-b lenient"
+            ]
+        );
+
+        // hsv was only tested by impl-a
+        assert_eq!(matrix.rate(0, 0), Some(100.0));
+        assert_eq!(matrix.rate(0, 2), None);
+
+        // operators was only tested by impl-b
+        assert_eq!(matrix.rate(1, 0), None);
+        assert_eq!(matrix.rate(1, 2), Some(50.0));
+        assert_eq!(matrix.rate(1, 3), Some(100.0));
+        assert_eq!(matrix.timeouts(1, 2), 1);
+        assert_eq!(matrix.timeouts(0, 0), 0);
+    }
+}
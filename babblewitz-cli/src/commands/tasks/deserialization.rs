@@ -1,50 +1,318 @@
-use crate::core::common::{calculate_impl_width, print_table_header};
+use crate::core::common::{calculate_impl_width, print_outcome_table, print_table_header};
 use crate::core::config::TaskType;
-use crate::core::executor::{Built, ExecutionResult, ImplementationExecutor};
+use crate::core::executor::{
+    Built, ExecutionResult, ImplementationExecutor, Outcome, OutcomeCounts,
+};
 use crate::core::implementation::Implementation;
+use crate::core::isolation::RunConditions;
+use crate::core::jobs;
+use crate::core::results;
+use crate::core::rusage::ResourceUsage;
 use crate::core::savefile::{find_save_files, Game, SaveFile};
+use crate::core::stats::{self, Summary};
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Inputs at least this large can't plausibly be processed in zero time
+const IMPLAUSIBLE_ZERO_TIME_BYTES: u64 = 1024 * 1024;
 
 #[derive(Debug, serde::Serialize)]
 pub struct PerformanceResult {
     pub game: Game,
     pub test_type: String,
     pub total_files: usize,
-    pub avg_throughput_mbps: f64,
+    /// Clock the throughput is measured by
+    pub clock: Clock,
+    /// Measured and warm-up runs of each file
+    pub iterations: usize,
+    pub warmup: usize,
+    /// Throughput of the measured runs, if any succeeded
+    pub throughput: Option<ThroughputStats>,
     pub total_data_mb: f64,
+    /// Total self-reported and wall time of the measured runs
+    pub self_reported_time: Duration,
+    pub wall_time: Duration,
+    /// How the runs turned out
+    pub outcomes: OutcomeCounts,
+    /// Why each unsuccessful file failed
     pub failed_files: Vec<String>,
+    /// Combined resource usage of the measured runs, when reported
+    pub usage: Option<ResourceUsage>,
+    /// Total time reported for each phase of the task, in the order reported
+    pub phases: Vec<(String, Duration)>,
+    pub warnings: Vec<String>,
+    /// Conditions the benchmarks ran under in isolation mode
+    pub conditions: Option<RunConditions>,
+    /// Results of each file, for saving every run's measurements
+    #[serde(skip)]
+    pub files: Vec<FileResult>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FileResult {
     pub game: Game,
     pub implementation: String,
+    pub file_name: String,
     pub data_size_bytes: u64, // Size of data sent to stdin (uncompressed)
     pub result: FileTestResult,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
 pub enum FileTestResult {
-    Success { elapsed_ms: u128 },
-    Failed,
+    /// Every run succeeded, with the measurements of each run after warm-up
+    Success { runs: Vec<RunMeasurement> },
+    /// The first run that didn't succeed
+    Failed { outcome: Outcome, error: String },
+}
+
+/// Measurements of a single successful run of a save file
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RunMeasurement {
+    pub timings: Timings,
+    pub usage: Option<ResourceUsage>,
+    pub phases: Vec<(String, Duration)>,
+    /// Warnings reported by the implementation
+    pub warnings: Vec<String>,
+}
+
+impl FileResult {
+    /// Throughput of each measured run by the given clock, in MB/s
+    pub fn throughputs(&self, clock: Clock) -> Vec<f64> {
+        match &self.result {
+            FileTestResult::Success { runs } => runs
+                .iter()
+                .filter_map(|run| run.timings.get(clock))
+                .map(|time| throughput_mbps(self.data_size_bytes, time))
+                .collect(),
+            FileTestResult::Failed { .. } => Vec::new(),
+        }
+    }
+
+    /// Warnings reported by the implementation and any implausible timing,
+    /// each listed once however many runs it occurred in
+    fn warnings(&self) -> Vec<String> {
+        let FileTestResult::Success { runs } = &self.result else {
+            return Vec::new();
+        };
+
+        let mut result = Vec::new();
+        for run in runs {
+            let implausibility = run.timings.implausibility(self.data_size_bytes);
+            for warning in run.warnings.iter().cloned().chain(implausibility) {
+                if !result.contains(&warning) {
+                    result.push(warning);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl FileTestResult {
+    pub fn outcome(&self) -> Outcome {
+        match self {
+            FileTestResult::Success { .. } => Outcome::Success,
+            FileTestResult::Failed { outcome, .. } => *outcome,
+        }
+    }
+}
+
+/// Which measurement of a run's duration throughput is calculated from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Clock {
+    /// The time implementations report for the task, excluding startup and
+    /// reading the input
+    #[default]
+    SelfReported,
+    /// Time from starting the implementation to its exit
+    Wall,
+    /// User and system CPU time used by the implementation (Linux only)
+    Cpu,
+}
+
+impl Clock {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Clock::SelfReported => "self-reported",
+            Clock::Wall => "wall",
+            Clock::Cpu => "cpu",
+        }
+    }
+}
+
+impl std::fmt::Display for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The duration of a successful run by each clock
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Timings {
+    pub self_reported: Duration,
+    pub wall: Duration,
+    pub cpu: Option<Duration>,
+}
+
+impl Timings {
+    pub fn get(&self, clock: Clock) -> Option<Duration> {
+        match clock {
+            Clock::SelfReported => Some(self.self_reported),
+            Clock::Wall => Some(self.wall),
+            Clock::Cpu => self.cpu,
+        }
+    }
+
+    /// Explain why the self-reported time can't be right for an input of the
+    /// given size, if it can't
+    pub fn implausibility(&self, data_size_bytes: u64) -> Option<String> {
+        if self.self_reported > self.wall {
+            Some(format!(
+                "Self-reported time of {:?} exceeds the measured wall time of {:?}",
+                self.self_reported, self.wall
+            ))
+        } else if self.self_reported.is_zero() && data_size_bytes >= IMPLAUSIBLE_ZERO_TIME_BYTES {
+            Some(format!(
+                "Self-reported time of zero for a {:.1} MB input",
+                data_size_bytes as f64 / (1024.0 * 1024.0)
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Statistics of the throughput of each measured run, in MB/s
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct ThroughputStats {
+    pub runs: usize,
+    pub median: f64,
+    pub min: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    /// Throughput that 95% of runs reached or exceeded (ie: the 95th
+    /// percentile of the time taken per megabyte)
+    pub p95: f64,
+}
+
+impl ThroughputStats {
+    pub fn of(throughputs: &[f64]) -> Option<ThroughputStats> {
+        let summary = Summary::of(throughputs)?;
+        Some(ThroughputStats {
+            runs: summary.count,
+            median: summary.median,
+            min: summary.min,
+            mean: summary.mean,
+            stddev: summary.stddev,
+            p95: stats::percentile(throughputs, 5.0)?,
+        })
+    }
+}
+
+/// How many times save files are run, and how many at once
+#[derive(Debug, Clone, Copy)]
+pub struct RunOptions {
+    pub jobs: usize,
+    /// Measured runs of each file
+    pub iterations: usize,
+    /// Runs of each file before the measured runs, which are discarded
+    pub warmup: usize,
+    /// CPU to pin every run to in isolation mode
+    pub isolate_cpu: Option<usize>,
+}
+
+impl RunOptions {
+    /// Check and report the conditions benchmarks will run under in
+    /// isolation mode, which runs one save file at a time
+    fn isolation_conditions(&self) -> Result<Option<RunConditions>> {
+        let Some(cpu) = self.isolate_cpu else {
+            return Ok(None);
+        };
+        if self.jobs > 1 {
+            anyhow::bail!(
+                "Isolation mode runs one save file at a time and can't be combined with --jobs {}",
+                self.jobs
+            );
+        }
+
+        let conditions = RunConditions::inspect(cpu)?;
+        println!("Isolation mode: {}", conditions.summary());
+        for warning in &conditions.warnings {
+            println!("  Warning: {}", warning);
+        }
+        Ok(Some(conditions))
+    }
 }
 
-#[derive(Debug)]
+/// Megabytes processed per second
+fn throughput_mbps(data_size_bytes: u64, time: Duration) -> f64 {
+    let mb_size = data_size_bytes as f64 / (1024.0 * 1024.0);
+    let seconds = time.as_secs_f64();
+    if seconds > 0.0 {
+        mb_size / seconds
+    } else {
+        0.0
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct PerformanceTable {
     pub files: Vec<FileResult>,
     pub implementations: Vec<String>,
+    /// Conditions the benchmarks ran under in isolation mode
+    #[serde(default)]
+    pub conditions: Option<RunConditions>,
 }
 
-pub fn run_impl_benchmarks(implementation_path: &Path) -> Result<Vec<PerformanceResult>> {
+impl PerformanceTable {
+    /// Write the measurements of every run to a JSON results file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        results::save(path, TaskType::Deserialization, self)
+    }
+
+    /// Read a results file written by [`PerformanceTable::save`]
+    pub fn load(path: &Path) -> Result<PerformanceTable> {
+        results::load(path, &[TaskType::Deserialization])
+    }
+
+    /// Combine the results of several runs of the benchmarks
+    pub fn from_results(results: &[PerformanceResult]) -> PerformanceTable {
+        let files: Vec<FileResult> = results.iter().flat_map(|x| x.files.clone()).collect();
+        let mut implementations: Vec<String> = Vec::new();
+        for file in &files {
+            if !implementations.contains(&file.implementation) {
+                implementations.push(file.implementation.clone());
+            }
+        }
+        PerformanceTable {
+            files,
+            implementations,
+            conditions: results.first().and_then(|x| x.conditions.clone()),
+        }
+    }
+}
+
+/// Run benchmarks for a single implementation, measuring throughput by the
+/// given clock
+pub fn run_impl_benchmarks(
+    implementation_path: &Path,
+    options: RunOptions,
+    clock: Clock,
+) -> Result<Vec<PerformanceResult>> {
     let implementation = Implementation::load_from_path(implementation_path)?;
-    run_implementation_benchmarks(&implementation)
+    run_implementation_benchmarks(&implementation, options, clock)
 }
 
 fn run_implementation_benchmarks(
     implementation: &Implementation,
+    options: RunOptions,
+    clock: Clock,
 ) -> Result<Vec<PerformanceResult>> {
+    let conditions = options.isolation_conditions()?;
     let performance_tasks = &[TaskType::Deserialization];
     let mut results = Vec::new();
     for task in performance_tasks {
@@ -52,7 +320,14 @@ fn run_implementation_benchmarks(
 
         for game in supported_games {
             println!("Running {} benchmark for game: {}", task, game);
-            let result = run_benchmark_with_implementation(implementation, &game, *task)?;
+            let result = run_benchmark_with_implementation(
+                implementation,
+                &game,
+                *task,
+                options,
+                clock,
+                conditions.as_ref(),
+            )?;
             results.push(result);
         }
     }
@@ -60,15 +335,18 @@ fn run_implementation_benchmarks(
     Ok(results)
 }
 
-/// Core function to run benchmarks on save files with a given executor
+/// Core function to run benchmarks on save files with a given executor. Each
+/// file is run for the warm-up and then the measured iterations, stopping at
+/// the first run that fails.
 fn run_benchmarks_on_files(
     executor: &ImplementationExecutor<'_, Built>,
     save_files: impl Iterator<Item = SaveFile>,
     task_type: TaskType,
+    options: RunOptions,
 ) -> Result<Vec<FileResult>> {
-    let mut file_results = Vec::new();
+    let save_files: Vec<SaveFile> = save_files.collect();
 
-    for save_file in save_files {
+    let file_results = jobs::parallel_map(&save_files, options.jobs, |save_file| {
         let file_data = save_file.read().with_context(|| {
             format!(
                 "Failed to read save file: {}",
@@ -77,34 +355,67 @@ fn run_benchmarks_on_files(
         })?;
         let data_size_bytes = file_data.len() as u64;
 
-        match executor.execute(&file_data, task_type, &[save_file.detected_game]) {
-            Ok(result) => {
-                let test_result = match result {
-                    ExecutionResult::Success { elapsed } => FileTestResult::Success {
-                        elapsed_ms: elapsed.as_millis(),
+        let text_path = save_file.text_path();
+        let mut runs = Vec::new();
+        let mut failure = None;
+        for iteration in 0..options.warmup + options.iterations {
+            let result = executor.execute_with_path(
+                &file_data,
+                text_path,
+                task_type,
+                &[save_file.detected_game],
+                None,
+            );
+
+            match result {
+                // A run that couldn't be carried out fails this file alone
+                Err(e) => {
+                    failure = Some(FileTestResult::Failed {
+                        outcome: Outcome::SpawnFailed,
+                        error: format!("{:#}", e),
+                    });
+                    break;
+                }
+                // Warm-up runs only prime caches and runtimes
+                Ok(ExecutionResult::Success { .. }) if iteration < options.warmup => {}
+                Ok(ExecutionResult::Success {
+                    elapsed,
+                    wall_time,
+                    usage,
+                    warnings,
+                    phases,
+                    ..
+                }) => runs.push(RunMeasurement {
+                    timings: Timings {
+                        self_reported: elapsed,
+                        wall: wall_time,
+                        cpu: usage.map(|x| x.cpu_time()),
                     },
-                    ExecutionResult::Error { .. } => FileTestResult::Failed,
-                };
-
-                file_results.push(FileResult {
-                    game: save_file.detected_game,
-                    implementation: executor.implementation().name.clone(),
-                    data_size_bytes,
-                    result: test_result,
-                });
-            }
-            Err(_) => {
-                file_results.push(FileResult {
-                    game: save_file.detected_game,
-                    implementation: executor.implementation().name.clone(),
-                    data_size_bytes,
-                    result: FileTestResult::Failed,
-                });
+                    usage,
+                    phases,
+                    warnings,
+                }),
+                Ok(result) => {
+                    failure = Some(FileTestResult::Failed {
+                        outcome: result.outcome(),
+                        error: result.error_message().unwrap_or_default(),
+                    });
+                    break;
+                }
             }
         }
-    }
+        let test_result = failure.unwrap_or(FileTestResult::Success { runs });
 
-    Ok(file_results)
+        Ok(FileResult {
+            game: save_file.detected_game,
+            implementation: executor.implementation().name.clone(),
+            file_name: save_file.file_name(),
+            data_size_bytes,
+            result: test_result,
+        })
+    });
+
+    file_results.into_iter().collect()
 }
 
 /// Ensure corpus directory exists and return the corpus path
@@ -124,8 +435,12 @@ fn run_benchmark_with_implementation(
     implementation: &Implementation,
     game: &Game,
     task_type: TaskType,
+    options: RunOptions,
+    clock: Clock,
+    conditions: Option<&RunConditions>,
 ) -> Result<PerformanceResult> {
-    let executor = ImplementationExecutor::build_implementation(implementation)?;
+    let executor = ImplementationExecutor::build_implementation(implementation)?
+        .pinned_to(options.isolate_cpu);
 
     let corpus_path = ensure_corpus_directory_exists()?;
 
@@ -136,57 +451,88 @@ fn run_benchmark_with_implementation(
 
     println!("  Running actual performance measurements...");
 
-    let file_results = run_benchmarks_on_files(&executor, filtered_files.into_iter(), task_type)?;
+    let file_results =
+        run_benchmarks_on_files(&executor, filtered_files.into_iter(), task_type, options)?;
 
     let mut throughputs = Vec::new();
+    let mut outcomes = OutcomeCounts::default();
     let mut failed_files = Vec::new();
+    let mut usage: Option<ResourceUsage> = None;
+    let mut self_reported_time = Duration::ZERO;
+    let mut wall_time = Duration::ZERO;
+    let mut phases: Vec<(String, Duration)> = Vec::new();
+    let mut warnings = Vec::new();
     let mut total_data_bytes = 0u64;
     let total_files = file_results.len();
 
-    for result in file_results {
+    for result in &file_results {
         total_data_bytes += result.data_size_bytes;
+        outcomes.record(result.result.outcome());
+        for warning in result.warnings() {
+            warnings.push(format!("{}: {}", result.file_name, warning));
+        }
+        throughputs.extend(result.throughputs(clock));
 
-        match result.result {
-            FileTestResult::Success { elapsed_ms } => {
-                // Calculate throughput: MB/s
-                let mb_size = result.data_size_bytes as f64 / (1024.0 * 1024.0);
-                let seconds = elapsed_ms as f64 / 1000.0;
-                let throughput = if seconds > 0.0 {
-                    mb_size / seconds
-                } else {
-                    0.0
-                };
-                throughputs.push(throughput);
+        match &result.result {
+            FileTestResult::Success { runs } => {
+                for run in runs {
+                    if let Some(run_usage) = run.usage {
+                        usage = Some(usage.map_or(run_usage, |x| x.combine(&run_usage)));
+                    }
+
+                    self_reported_time += run.timings.self_reported;
+                    wall_time += run.timings.wall;
+                    for (name, elapsed) in &run.phases {
+                        match phases.iter_mut().find(|(x, _)| x == name) {
+                            Some((_, total)) => *total += *elapsed,
+                            None => phases.push((name.clone(), *elapsed)),
+                        }
+                    }
+                }
             }
-            FileTestResult::Failed => {
-                failed_files.push(format!("File failed: {:?}", result.game));
+            FileTestResult::Failed { error, .. } => {
+                failed_files.push(format!("{}: {}", result.file_name, error));
             }
         }
     }
 
-    let avg_throughput_mbps = if throughputs.is_empty() {
-        0.0
-    } else {
-        throughputs.iter().sum::<f64>() / throughputs.len() as f64
-    };
-
     let total_data_mb = total_data_bytes as f64 / (1024.0 * 1024.0);
 
     Ok(PerformanceResult {
         game: *game,
         test_type: task_type.to_string(),
         total_files,
-        avg_throughput_mbps,
+        clock,
+        iterations: options.iterations,
+        warmup: options.warmup,
+        throughput: ThroughputStats::of(&throughputs),
         total_data_mb,
+        self_reported_time,
+        wall_time,
+        outcomes,
         failed_files,
+        usage,
+        phases,
+        warnings,
+        conditions: conditions.cloned(),
+        files: file_results,
     })
 }
 
 /// Run benchmark tests across all implementations and return table data
-pub fn run_benchmark_table() -> Result<PerformanceTable> {
+pub fn run_benchmark_table(options: RunOptions) -> Result<PerformanceTable> {
     // Find all implementations that support deserialization
     let implementations =
         crate::core::implementation::find_implementations_for_task(TaskType::Deserialization)?;
+    run_benchmark_table_for(&implementations, options)
+}
+
+/// Run benchmark tests for the given implementations and return table data
+pub fn run_benchmark_table_for(
+    implementations: &[Implementation],
+    options: RunOptions,
+) -> Result<PerformanceTable> {
+    let conditions = options.isolation_conditions()?;
 
     // Ensure corpus assets are available
     let corpus_path = ensure_corpus_directory_exists()?;
@@ -195,7 +541,7 @@ pub fn run_benchmark_table() -> Result<PerformanceTable> {
     // Run tests for each implementation
     let mut all_file_results = Vec::new();
 
-    for implementation in &implementations {
+    for implementation in implementations {
         println!("Testing implementation: {}", implementation.name);
 
         // Check which games this implementation supports
@@ -214,7 +560,7 @@ pub fn run_benchmark_table() -> Result<PerformanceTable> {
 
         // Build the executor once per implementation
         let executor = match ImplementationExecutor::build_implementation(implementation) {
-            Ok(executor) => executor,
+            Ok(executor) => executor.pinned_to(options.isolate_cpu),
             Err(e) => {
                 println!("  Failed to build {}: {}", implementation.name, e);
                 // Add failed results for all files this implementation should support
@@ -222,8 +568,12 @@ pub fn run_benchmark_table() -> Result<PerformanceTable> {
                     all_file_results.push(FileResult {
                         game: save_file.detected_game,
                         implementation: implementation.name.clone(),
+                        file_name: save_file.file_name(),
                         data_size_bytes: 0,
-                        result: FileTestResult::Failed,
+                        result: FileTestResult::Failed {
+                            outcome: Outcome::SpawnFailed,
+                            error: format!("{:#}", e),
+                        },
                     });
                 }
                 continue;
@@ -234,6 +584,7 @@ pub fn run_benchmark_table() -> Result<PerformanceTable> {
             &executor,
             supported_files.into_iter(),
             TaskType::Deserialization,
+            options,
         )?;
 
         all_file_results.extend(file_results);
@@ -247,11 +598,24 @@ pub fn run_benchmark_table() -> Result<PerformanceTable> {
     Ok(PerformanceTable {
         files: all_file_results,
         implementations: implementation_names,
+        conditions,
     })
 }
 
-/// Print benchmark results as a table (transposed: implementations as rows, games as columns)
-pub fn print_benchmark_table(table: &PerformanceTable) {
+/// Measured runs and failure marker for an implementation and game
+#[derive(Default)]
+struct GameSummary<'a> {
+    throughputs: Vec<f64>,
+    usages: Vec<ResourceUsage>,
+    /// Marker shown in place of the results, timeouts taking precedence over failures
+    marker: Option<&'a str>,
+}
+
+/// Print benchmark results as a table (transposed: implementations as rows,
+/// games as columns). Each game has throughput statistics measured by the
+/// given clock and a peak memory column, and optionally the average user and
+/// system CPU time per run.
+pub fn print_benchmark_table(table: &PerformanceTable, clock: Clock, cpu: bool) {
     // Extract unique games from the results directly
     let mut games_set = std::collections::HashSet::new();
     for result in &table.files {
@@ -261,91 +625,210 @@ pub fn print_benchmark_table(table: &PerformanceTable) {
     games.sort();
 
     // Group results by implementation and game for averaging
-    let mut impl_game_results: HashMap<String, HashMap<Game, Vec<f64>>> = HashMap::new();
-    let mut impl_game_failures: HashMap<String, HashMap<Game, bool>> = HashMap::new();
+    let mut summaries: HashMap<(&str, Game), GameSummary> = HashMap::new();
 
     for result in &table.files {
-        let impl_name = &result.implementation;
+        let summary = summaries
+            .entry((result.implementation.as_str(), result.game))
+            .or_default();
 
         match &result.result {
-            FileTestResult::Success { elapsed_ms } => {
-                // Calculate throughput for successful results
-                let mb_size = result.data_size_bytes as f64 / (1024.0 * 1024.0);
-                let seconds = *elapsed_ms as f64 / 1000.0;
-                let throughput = if seconds > 0.0 {
-                    mb_size / seconds
-                } else {
-                    0.0
-                };
-
-                impl_game_results
-                    .entry(impl_name.clone())
-                    .or_default()
-                    .entry(result.game)
-                    .or_default()
-                    .push(throughput);
+            FileTestResult::Success { runs } => {
+                summary.throughputs.extend(result.throughputs(clock));
+                for run in runs {
+                    summary.usages.extend(run.usage);
+                }
+            }
+            FileTestResult::Failed {
+                outcome: Outcome::TimedOut,
+                ..
+            } => {
+                summary.marker = Some("⏱");
             }
-            FileTestResult::Failed => {
+            FileTestResult::Failed { .. } => {
                 // Mark this implementation/game combination as having failures
-                impl_game_failures
-                    .entry(impl_name.clone())
-                    .or_default()
-                    .insert(result.game, true);
+                summary.marker.get_or_insert("⚠️");
             }
         }
     }
 
+    let mut columns = Vec::new();
+    for game in &games {
+        columns.push(game.to_string());
+        for stat in ["min", "mean", "sd", "p95", "mem"] {
+            columns.push(format!("{} {}", game, stat));
+        }
+        if cpu {
+            columns.push(format!("{} user", game));
+            columns.push(format!("{} sys", game));
+        }
+    }
+
     // Calculate column widths
     let max_impl_width = calculate_impl_width(&table.implementations);
     let game_col_width = 12; // Fixed width for game columns
 
     // Print header
-    print_table_header(max_impl_width, &games, game_col_width);
+    print_table_header(max_impl_width, &columns, game_col_width);
 
     // Print data rows
     for impl_name in &table.implementations {
         print!("{:<width$} ", impl_name, width = max_impl_width);
 
-        // Print average throughput for each game, or caution emoji if there are failures
         for game in &games {
-            let display_value = if let Some(failures) = impl_game_failures.get(impl_name) {
-                if *failures.get(game).unwrap_or(&false) {
-                    "⚠️".to_string()
-                } else if let Some(game_results) = impl_game_results.get(impl_name) {
-                    if let Some(throughputs) = game_results.get(game) {
-                        if !throughputs.is_empty() {
-                            let avg_throughput =
-                                throughputs.iter().sum::<f64>() / throughputs.len() as f64;
-                            format!("{:.1} MB/s", avg_throughput)
-                        } else {
-                            "".to_string()
-                        }
-                    } else {
-                        "".to_string()
-                    }
-                } else {
-                    "".to_string()
-                }
-            } else if let Some(game_results) = impl_game_results.get(impl_name) {
-                if let Some(throughputs) = game_results.get(game) {
-                    if !throughputs.is_empty() {
-                        let avg_throughput =
-                            throughputs.iter().sum::<f64>() / throughputs.len() as f64;
-                        format!("{:.1} MB/s", avg_throughput)
-                    } else {
-                        "".to_string()
-                    }
-                } else {
-                    "".to_string()
-                }
-            } else {
-                "".to_string()
-            };
+            let summary = summaries.get(&(impl_name.as_str(), *game));
+            let mut values = game_columns(summary, cpu);
+            if let Some(marker) = summary.and_then(|x| x.marker) {
+                // Show a failure or timeout instead of the median throughput
+                values[0] = marker.to_string();
+            }
 
-            print!("{:>width$} ", display_value, width = game_col_width);
+            for value in values {
+                print!("{:>width$} ", value, width = game_col_width);
+            }
         }
         println!();
     }
+
+    if let Some(conditions) = &table.conditions {
+        print_conditions(conditions);
+    }
+}
+
+/// Print the conditions benchmarks ran under in isolation mode
+fn print_conditions(conditions: &RunConditions) {
+    println!();
+    println!("Run conditions: {}", conditions.summary());
+    for warning in &conditions.warnings {
+        println!("  Warning: {}", warning);
+    }
+}
+
+/// Format the median, minimum, mean, standard deviation, and 95th percentile
+/// throughput, peak memory, and optionally average CPU times of an
+/// implementation's measured runs for a game
+fn game_columns(summary: Option<&GameSummary>, cpu: bool) -> Vec<String> {
+    let column_count = if cpu { 8 } else { 6 };
+    let Some(stats) = summary.and_then(|x| ThroughputStats::of(&x.throughputs)) else {
+        return vec![String::new(); column_count];
+    };
+    let summary = summary.unwrap();
+
+    let mut values: Vec<String> = [stats.median, stats.min, stats.mean, stats.stddev, stats.p95]
+        .iter()
+        .map(|x| format_throughput(*x))
+        .collect();
+
+    let usage = summary.usages.iter().copied().reduce(|a, b| a.combine(&b));
+    values.push(usage.map_or_else(String::new, |x| format_memory(x.max_rss_bytes)));
+
+    if cpu {
+        let runs = summary.usages.len() as u32;
+        values.push(usage.map_or_else(String::new, |x| format_duration(x.user_time / runs)));
+        values.push(usage.map_or_else(String::new, |x| format_duration(x.system_time / runs)));
+    }
+
+    values
+}
+
+fn format_memory(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+fn format_throughput(mbps: f64) -> String {
+    format!("{:.1} MB/s", mbps)
+}
+
+/// Format a duration to microsecond precision in a readable unit
+fn format_duration(time: Duration) -> String {
+    if time >= Duration::from_secs(1) {
+        format!("{:.3}s", time.as_secs_f64())
+    } else if time >= Duration::from_millis(1) {
+        format!("{:.3}ms", time.as_secs_f64() * 1000.0)
+    } else {
+        format!("{}µs", time.as_micros())
+    }
+}
+
+/// How files with an unsuccessful outcome are described in results
+fn outcome_description(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Success => "succeeded",
+        Outcome::Rejected => "rejected",
+        Outcome::Crashed => "crashed",
+        Outcome::NonzeroExit => "exited with an error",
+        Outcome::MalformedOutput => "with malformed output",
+        Outcome::SpawnFailed => "failed to start",
+        Outcome::LimitExceeded => "over a resource limit",
+        Outcome::TimedOut => "timed out",
+    }
+}
+
+/// Print how many runs of each implementation succeeded or failed in each
+/// way, followed by why each failed run failed
+pub fn print_outcome_details(table: &PerformanceTable) {
+    let outcomes: Vec<(String, OutcomeCounts)> = table
+        .implementations
+        .iter()
+        .map(|name| {
+            let mut counts = OutcomeCounts::default();
+            for result in table.files.iter().filter(|x| &x.implementation == name) {
+                counts.record(result.result.outcome());
+            }
+            (name.clone(), counts)
+        })
+        .filter(|(_, counts)| counts != &OutcomeCounts::default())
+        .collect();
+    print_outcome_table(&outcomes);
+
+    let failures: Vec<_> = table
+        .files
+        .iter()
+        .filter_map(|result| match &result.result {
+            FileTestResult::Failed { error, .. } => Some((result, error)),
+            FileTestResult::Success { .. } => None,
+        })
+        .collect();
+    if failures.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("Failed save files:");
+    for (result, error) in failures {
+        println!(
+            "{} ({}): {}",
+            result.implementation, result.file_name, error
+        );
+    }
+}
+
+/// Print the warnings implementations reported and the runs whose
+/// self-reported time can't be right
+pub fn print_warnings(table: &PerformanceTable) {
+    let warnings: Vec<_> = table
+        .files
+        .iter()
+        .flat_map(|result| {
+            result
+                .warnings()
+                .into_iter()
+                .map(move |warning| (result, warning))
+        })
+        .collect();
+
+    if warnings.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("Warnings:");
+    for (result, warning) in warnings {
+        println!(
+            "{} ({}): {}",
+            result.implementation, result.file_name, warning
+        );
+    }
 }
 
 pub fn print_benchmark_results(results: &[PerformanceResult]) -> Result<()> {
@@ -355,11 +838,65 @@ pub fn print_benchmark_results(results: &[PerformanceResult]) -> Result<()> {
         println!("  Files tested: {}", result.total_files);
         println!("  Total data processed: {:.1} MB", result.total_data_mb);
         println!(
-            "  Average throughput: {:.1} MB/s",
-            result.avg_throughput_mbps
+            "  Runs per file: {} measured, {} warm-up",
+            result.iterations, result.warmup
         );
-        if !result.failed_files.is_empty() {
-            println!("  Failed files: {}", result.failed_files.len());
+        if let Some(stats) = &result.throughput {
+            println!(
+                "  Throughput ({} time, {} runs): median {}, min {}, mean {}, stddev {}, p95 {}",
+                result.clock,
+                stats.runs,
+                format_throughput(stats.median),
+                format_throughput(stats.min),
+                format_throughput(stats.mean),
+                format_throughput(stats.stddev),
+                format_throughput(stats.p95)
+            );
+        }
+        println!(
+            "  Total time: {} self-reported, {} wall",
+            format_duration(result.self_reported_time),
+            format_duration(result.wall_time)
+        );
+        for outcome in Outcome::ALL {
+            let count = result.outcomes.get(outcome);
+            if outcome != Outcome::Success && count > 0 {
+                println!("  Files {}: {}", outcome_description(outcome), count);
+            }
+        }
+        for failure in &result.failed_files {
+            println!("    {}", failure);
+        }
+        if let Some(usage) = &result.usage {
+            println!("  Peak memory: {}", format_memory(usage.max_rss_bytes));
+            println!(
+                "  CPU time: {} ({} user, {} system)",
+                format_duration(usage.cpu_time()),
+                format_duration(usage.user_time),
+                format_duration(usage.system_time)
+            );
+            println!(
+                "  Page faults: {} major, {} minor",
+                usage.major_faults, usage.minor_faults
+            );
+            println!(
+                "  Context switches: {} voluntary, {} involuntary",
+                usage.voluntary_context_switches, usage.involuntary_context_switches
+            );
+        }
+        if !result.phases.is_empty() {
+            let phases: Vec<String> = result
+                .phases
+                .iter()
+                .map(|(name, elapsed)| format!("{} {}", name, format_duration(*elapsed)))
+                .collect();
+            println!("  Phases: {}", phases.join(", "));
+        }
+        for warning in &result.warnings {
+            println!("  Warning: {}", warning);
+        }
+        if let Some(conditions) = &result.conditions {
+            println!("  Run conditions: {}", conditions.summary());
         }
         println!();
     }
@@ -371,21 +908,41 @@ pub fn print_benchmark_results(results: &[PerformanceResult]) -> Result<()> {
 mod tests {
     use super::*;
 
+    fn measurement(timings: Timings) -> RunMeasurement {
+        RunMeasurement {
+            timings,
+            usage: None,
+            phases: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
     #[test]
     fn test_file_test_result_types() {
         // Test Success result
-        let success = FileTestResult::Success { elapsed_ms: 1500 };
+        let timings = Timings {
+            self_reported: Duration::from_millis(1500),
+            wall: Duration::from_millis(1600),
+            cpu: None,
+        };
+        let success = FileTestResult::Success {
+            runs: vec![measurement(timings)],
+        };
         match success {
-            FileTestResult::Success { elapsed_ms } => assert_eq!(elapsed_ms, 1500),
+            FileTestResult::Success { runs } => {
+                let timings = runs[0].timings;
+                assert_eq!(timings.get(Clock::Wall), Some(Duration::from_millis(1600)));
+                assert_eq!(timings.get(Clock::Cpu), None);
+            }
             _ => panic!("Expected Success variant"),
         }
 
         // Test Failed result
-        let failed = FileTestResult::Failed;
-        match failed {
-            FileTestResult::Failed => {}
-            _ => panic!("Expected Failed variant"),
-        }
+        let failed = FileTestResult::Failed {
+            outcome: Outcome::Crashed,
+            error: String::from("Crashed (SIGSEGV): no output"),
+        };
+        assert_eq!(failed.outcome(), Outcome::Crashed);
     }
 
     #[test]
@@ -394,23 +951,146 @@ mod tests {
             FileResult {
                 game: Game::Eu4,
                 implementation: "jomini-reader".to_string(),
+                file_name: "a.eu4".to_string(),
                 data_size_bytes: 1024,
-                result: FileTestResult::Success { elapsed_ms: 100 },
+                result: FileTestResult::Success {
+                    runs: vec![measurement(Timings {
+                        self_reported: Duration::from_millis(100),
+                        wall: Duration::from_millis(120),
+                        cpu: None,
+                    })],
+                },
             },
             FileResult {
                 game: Game::Ck3,
                 implementation: "jomini-reader".to_string(),
+                file_name: "b.ck3".to_string(),
                 data_size_bytes: 2048,
-                result: FileTestResult::Failed,
+                result: FileTestResult::Failed {
+                    outcome: Outcome::TimedOut,
+                    error: String::from("Timed out after 60s"),
+                },
             },
         ];
 
         let table = PerformanceTable {
             files,
             implementations: vec!["jomini-reader".to_string()],
+            conditions: None,
         };
 
         assert_eq!(table.files.len(), 2);
         assert_eq!(table.implementations.len(), 1);
+
+        // Tables survive a round trip through a results file
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.json");
+        table.save(&path).unwrap();
+        let loaded = PerformanceTable::load(&path).unwrap();
+        assert_eq!(loaded.implementations, table.implementations);
+        assert_eq!(loaded.files.len(), 2);
+        assert_eq!(
+            loaded.files[0].throughputs(Clock::Wall),
+            table.files[0].throughputs(Clock::Wall)
+        );
+        assert_eq!(loaded.files[1].result.outcome(), Outcome::TimedOut);
+    }
+
+    #[test]
+    fn test_implausible_timings() {
+        let timings = |self_reported: u64, wall: u64| Timings {
+            self_reported: Duration::from_micros(self_reported),
+            wall: Duration::from_micros(wall),
+            cpu: None,
+        };
+        let mb = 1024 * 1024;
+
+        assert_eq!(timings(900, 1000).implausibility(100 * mb), None);
+        assert_eq!(timings(0, 1000).implausibility(10), None);
+        assert!(timings(1001, 1000)
+            .implausibility(10)
+            .unwrap()
+            .contains("exceeds the measured wall time"));
+        assert_eq!(
+            timings(0, 1000).implausibility(100 * mb).unwrap(),
+            "Self-reported time of zero for a 100.0 MB input"
+        );
+    }
+
+    #[test]
+    fn test_warnings_listed_once() {
+        let run = |self_reported: u64| RunMeasurement {
+            warnings: vec![String::from("duplicate key")],
+            ..measurement(Timings {
+                self_reported: Duration::from_micros(self_reported),
+                wall: Duration::from_micros(1000),
+                cpu: None,
+            })
+        };
+        let result = FileResult {
+            game: Game::Eu4,
+            implementation: String::from("jomini-reader"),
+            file_name: String::from("a.eu4"),
+            data_size_bytes: 10,
+            result: FileTestResult::Success {
+                runs: vec![run(500), run(500), run(2000)],
+            },
+        };
+
+        let warnings = result.warnings();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0], "duplicate key");
+        assert!(warnings[1].contains("exceeds the measured wall time"));
+    }
+
+    #[test]
+    fn test_throughput_stats() {
+        let throughputs: Vec<f64> = (1..=20).map(|x| f64::from(x) * 10.0).collect();
+        let stats = ThroughputStats::of(&throughputs).unwrap();
+        assert_eq!(stats.runs, 20);
+        assert_eq!(stats.median, 105.0);
+        assert_eq!(stats.min, 10.0);
+        assert_eq!(stats.mean, 105.0);
+        assert!((stats.p95 - 19.5).abs() < 1e-9);
+        assert_eq!(ThroughputStats::of(&[]), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_micros(15)), "15µs");
+        assert_eq!(format_duration(Duration::from_micros(1500)), "1.500ms");
+        assert_eq!(format_duration(Duration::from_millis(2500)), "2.500s");
+        assert_eq!(format_duration(Duration::ZERO), "0µs");
+    }
+
+    #[test]
+    fn test_game_columns() {
+        let usage = |mb: u64, user_ms: u64| ResourceUsage {
+            max_rss_bytes: mb * 1024 * 1024,
+            user_time: Duration::from_millis(user_ms),
+            ..Default::default()
+        };
+        let summary = GameSummary {
+            throughputs: vec![100.0, 200.0],
+            usages: vec![usage(30, 100), usage(50, 300)],
+            marker: None,
+        };
+
+        let stats = [
+            "150.0 MB/s",
+            "100.0 MB/s",
+            "150.0 MB/s",
+            "70.7 MB/s",
+            "105.0 MB/s",
+        ];
+        assert_eq!(
+            game_columns(Some(&summary), false),
+            [&stats[..], &["50.0 MB"]].concat()
+        );
+        assert_eq!(
+            game_columns(Some(&summary), true),
+            [&stats[..], &["50.0 MB", "200.000ms", "0µs"]].concat()
+        );
+        assert_eq!(game_columns(None, false), vec![String::new(); 6]);
     }
 }
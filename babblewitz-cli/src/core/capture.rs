@@ -0,0 +1,77 @@
+use std::io::Read;
+
+/// Output captured from an implementation up to a limit, counting the bytes
+/// that didn't fit rather than buffering without bound
+#[derive(Debug)]
+pub struct CappedBuffer {
+    data: Vec<u8>,
+    limit: usize,
+    omitted: u64,
+}
+
+impl CappedBuffer {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            data: Vec::new(),
+            limit,
+            omitted: 0,
+        }
+    }
+
+    pub fn extend(&mut self, bytes: &[u8]) {
+        let fits = bytes.len().min(self.limit - self.data.len());
+        self.data.extend_from_slice(&bytes[..fits]);
+        self.omitted += (bytes.len() - fits) as u64;
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.omitted > 0
+    }
+
+    /// The captured output, followed by a marker when some was left out
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        if self.is_truncated() {
+            let marker = format!("\n[truncated {} bytes]", self.omitted);
+            self.data.extend_from_slice(marker.as_bytes());
+        }
+        self.data
+    }
+}
+
+/// Drain the pipe to its end, capturing up to the limit
+pub fn read_capped(pipe: Option<impl Read>, limit: usize) -> std::io::Result<CappedBuffer> {
+    let mut buffer = CappedBuffer::new(limit);
+    let Some(mut pipe) = pipe else {
+        return Ok(buffer);
+    };
+
+    let mut chunk = [0; 8192];
+    loop {
+        match pipe.read(&mut chunk) {
+            Ok(0) => return Ok(buffer),
+            Ok(n) => buffer.extend(&chunk[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_capped() {
+        let buffer = read_capped(Some(&b"hello"[..]), 10).unwrap();
+        assert!(!buffer.is_truncated());
+        assert_eq!(buffer.into_bytes(), b"hello");
+
+        let input = vec![b'a'; 20_000];
+        let buffer = read_capped(Some(&input[..]), 4).unwrap();
+        assert!(buffer.is_truncated());
+        assert_eq!(buffer.into_bytes(), b"aaaa\n[truncated 19996 bytes]");
+
+        let buffer = read_capped(None::<&[u8]>, 4).unwrap();
+        assert_eq!(buffer.into_bytes(), b"");
+    }
+}
@@ -1,14 +1,28 @@
+use crate::core::executor::{Outcome, OutcomeCounts};
+
 /// Helper function to print table headers with consistent formatting
 pub fn print_table_header(
     implementation_width: usize,
     columns: &[impl AsRef<str>],
     column_width: usize,
 ) {
-    print!(
-        "{:<width$} ",
+    print_labeled_table_header(
         "Implementation",
-        width = implementation_width
+        implementation_width,
+        columns,
+        column_width,
     );
+}
+
+/// Print a table header whose first column is labeled with something other
+/// than implementations
+pub fn print_labeled_table_header(
+    label: &str,
+    label_width: usize,
+    columns: &[impl AsRef<str>],
+    column_width: usize,
+) {
+    print!("{:<width$} ", label, width = label_width);
     for column in columns {
         print!(
             "{:>width$} ",
@@ -19,7 +33,7 @@ pub fn print_table_header(
     println!();
 
     // Print separator line
-    print!("{} ", "-".repeat(implementation_width));
+    print!("{} ", "-".repeat(label_width));
     for _ in columns {
         print!("{} ", "-".repeat(column_width));
     }
@@ -28,9 +42,36 @@ pub fn print_table_header(
 
 /// Calculate maximum implementation name width for table formatting
 pub fn calculate_impl_width(implementations: &[String]) -> usize {
-    implementations
+    calculate_label_width("Implementation", implementations)
+}
+
+/// Calculate the width of a table's first column from its label and row names
+pub fn calculate_label_width(label: &str, names: &[String]) -> usize {
+    names
         .iter()
-        .map(|impl_name| impl_name.len())
-        .fold("Implementation".len(), |max, len| max.max(len))
+        .map(|name| name.len())
+        .fold(label.len(), |max, len| max.max(len))
         .max(15)
 }
+
+/// Print how many executions had each outcome for every implementation
+pub fn print_outcome_table(outcomes: &[(String, OutcomeCounts)]) {
+    if outcomes.is_empty() {
+        return;
+    }
+
+    let names: Vec<String> = outcomes.iter().map(|(name, _)| name.clone()).collect();
+    let columns = Outcome::ALL.map(|outcome| outcome.as_str());
+    let impl_width = calculate_impl_width(&names);
+    let column_width = columns.iter().map(|c| c.len()).fold(8, usize::max);
+
+    println!("\nExecution outcomes:");
+    print_table_header(impl_width, &columns, column_width);
+    for (name, counts) in outcomes {
+        print!("{:<width$} ", name, width = impl_width);
+        for outcome in Outcome::ALL {
+            print!("{:>width$} ", counts.get(outcome), width = column_width);
+        }
+        println!();
+    }
+}
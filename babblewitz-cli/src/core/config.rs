@@ -1,12 +1,22 @@
 use crate::core::savefile::Game;
+use crate::core::version::{GameVersion, VersionRange};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long a single run of an implementation may take when no timeout is configured
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How much of each output stream is kept when no maximum is configured
+pub const DEFAULT_MAX_OUTPUT_KB: u64 = 16 * 1024;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum TaskType {
     CanParse,
     Deserialization,
+    Localisation,
 }
 
 impl TaskType {
@@ -14,6 +24,7 @@ impl TaskType {
         match self {
             TaskType::CanParse => "can-parse",
             TaskType::Deserialization => "deserialization",
+            TaskType::Localisation => "localisation",
         }
     }
 }
@@ -52,12 +63,117 @@ impl std::fmt::Display for ProjectType {
     }
 }
 
+/// How the runner communicates with an implementation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    /// A new process for every input
+    #[default]
+    Process,
+    /// A single long running process that is sent framed requests
+    Server,
+}
+
+impl Protocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Process => "process",
+            Protocol::Server => "server",
+        }
+    }
+}
+
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// How an implementation receives the input for a task
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputMode {
+    /// Piped to stdin
+    #[default]
+    Stdin,
+    /// A path to a file holding the input, passed as `--input <path>`
+    File,
+}
+
+impl InputMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InputMode::Stdin => "stdin",
+            InputMode::File => "file",
+        }
+    }
+}
+
+impl std::fmt::Display for InputMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Format of the output implementations write for each input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProtocolVersion {
+    /// Elapsed microseconds on the first line followed by the result
+    #[default]
+    V1,
+    /// A single JSON object
+    V2,
+}
+
+impl ProtocolVersion {
+    pub fn from_number(number: u64) -> Option<Self> {
+        match number {
+            1 => Some(ProtocolVersion::V1),
+            2 => Some(ProtocolVersion::V2),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self) -> u64 {
+        match self {
+            ProtocolVersion::V1 => 1,
+            ProtocolVersion::V2 => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+impl Serialize for ProtocolVersion {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.number())
+    }
+}
+
+impl<'de> Deserialize<'de> for ProtocolVersion {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let number = u64::deserialize(deserializer)?;
+        ProtocolVersion::from_number(number).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "unsupported protocol version: {} (expected 1 or 2)",
+                number
+            ))
+        })
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ImplementationConfig {
     pub name: String,
     pub description: Option<String>,
     #[serde(rename = "project-type")]
     pub project_type: ProjectType,
+    #[serde(rename = "protocol-version", default)]
+    pub protocol_version: ProtocolVersion,
     pub execution: Option<ExecutionConfig>,
     pub tasks: HashMap<TaskType, TaskConfig>,
 }
@@ -68,11 +184,120 @@ pub struct ExecutionConfig {
     pub build_command: Option<String>,
     #[serde(rename = "run-command")]
     pub run_command: Option<String>,
+    /// Seconds a single run may take before it is killed, for every task
+    #[serde(default, with = "optional_seconds")]
+    pub timeout: Option<Duration>,
+    #[serde(default)]
+    pub protocol: Protocol,
+    /// Environment variables for building and running the implementation
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Directory to build and run from, relative to the implementation
+    #[serde(rename = "working-dir", skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+    /// Start from an empty environment rather than inheriting the host's,
+    /// keeping only what is needed to find toolchains
+    #[serde(rename = "clear-env", default)]
+    pub clear_env: bool,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub limits: ResourceLimits,
+    /// Kilobytes of stdout and stderr each kept from a run
+    #[serde(
+        rename = "max-output-kb",
+        default,
+        deserialize_with = "positive_kb",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_output_kb: Option<u64>,
+}
+
+/// Operating system limits applied to each run of an implementation on Linux
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ResourceLimits {
+    /// Largest address space in megabytes
+    #[serde(rename = "memory-mb", skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// CPU time in seconds
+    #[serde(rename = "cpu-seconds", skip_serializing_if = "Option::is_none")]
+    pub cpu_seconds: Option<u64>,
+    /// Number of file descriptors that may be open at once
+    #[serde(rename = "open-files", skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<u64>,
+    /// Number of processes and threads the user may have, which includes
+    /// those outside the implementation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processes: Option<u64>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TaskConfig {
     pub games: Vec<Game>,
+    /// Game versions supported, when limited to a range of patches
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub versions: HashMap<Game, VersionRange>,
+    /// Seconds a single run may take before it is killed, overriding the
+    /// execution timeout
+    #[serde(
+        default,
+        with = "optional_seconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<Duration>,
+    /// How the input is delivered, stdin unless the task asks for a file
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub input: InputMode,
+    /// Environment variables for running the task, overriding the execution
+    /// variables of the same name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Directory to run the task from, relative to the implementation and
+    /// overriding the execution working directory
+    #[serde(rename = "working-dir", skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// (De)serialize an optional duration as a number of seconds
+mod optional_seconds {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(value: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => s.serialize_some(&duration.as_secs_f64()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        let Some(seconds) = Option::<f64>::deserialize(d)? else {
+            return Ok(None);
+        };
+
+        Duration::try_from_secs_f64(seconds)
+            .ok()
+            .filter(|duration| !duration.is_zero())
+            .map(Some)
+            .ok_or_else(|| {
+                D::Error::custom(format!(
+                    "invalid timeout: {} (expected a positive number of seconds)",
+                    seconds
+                ))
+            })
+    }
+}
+
+/// Deserialize an optional output limit, which must keep at least a kilobyte
+fn positive_kb<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<u64>, D::Error> {
+    match Option::<u64>::deserialize(d)? {
+        Some(0) => Err(serde::de::Error::custom(
+            "invalid max-output-kb: 0 (expected a positive number of kilobytes)",
+        )),
+        kb => Ok(kb),
+    }
 }
 
 impl ImplementationConfig {
@@ -89,6 +314,99 @@ impl ImplementationConfig {
             .map(|task_config| task_config.games.clone())
             .unwrap_or_default()
     }
+
+    /// Whether a task's support for a game extends to the given version or later
+    pub fn supports_version_for_task(
+        &self,
+        task: TaskType,
+        game: Game,
+        version: &GameVersion,
+    ) -> bool {
+        self.supported_versions_for_task(task, game)
+            .is_none_or(|range| range.reaches(version))
+    }
+
+    /// How the runner communicates with the implementation
+    pub fn protocol(&self) -> Protocol {
+        self.execution
+            .as_ref()
+            .map(|execution| execution.protocol)
+            .unwrap_or_default()
+    }
+
+    /// How the implementation receives the input for a task
+    pub fn input_for_task(&self, task: TaskType) -> InputMode {
+        self.tasks
+            .get(&task)
+            .map(|task_config| task_config.input)
+            .unwrap_or_default()
+    }
+
+    /// Environment variables for a task, or for building when there is no
+    /// task: the execution variables followed by the task's
+    pub fn env_for_task(&self, task: Option<TaskType>) -> Vec<(&str, &str)> {
+        let execution = self.execution.iter().flat_map(|x| &x.env);
+        let task = task
+            .and_then(|task| self.tasks.get(&task))
+            .into_iter()
+            .flat_map(|task_config| &task_config.env);
+        execution
+            .chain(task)
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect()
+    }
+
+    /// Directory relative to the implementation to run a task from, or to
+    /// build from when there is no task
+    pub fn working_dir_for_task(&self, task: Option<TaskType>) -> Option<&Path> {
+        task.and_then(|task| self.tasks.get(&task))
+            .and_then(|task_config| task_config.working_dir.as_deref())
+            .or_else(|| {
+                self.execution
+                    .as_ref()
+                    .and_then(|x| x.working_dir.as_deref())
+            })
+    }
+
+    /// Operating system limits for each run of the implementation
+    pub fn limits(&self) -> ResourceLimits {
+        self.execution
+            .as_ref()
+            .map(|execution| execution.limits)
+            .unwrap_or_default()
+    }
+
+    /// Bytes of stdout and stderr each kept from a run
+    pub fn max_output_bytes(&self) -> usize {
+        let kb = self
+            .execution
+            .as_ref()
+            .and_then(|x| x.max_output_kb)
+            .unwrap_or(DEFAULT_MAX_OUTPUT_KB);
+        usize::try_from(kb.saturating_mul(1024)).unwrap_or(usize::MAX)
+    }
+
+    /// Whether the implementation starts from an empty environment
+    pub fn clear_env(&self) -> bool {
+        self.execution.as_ref().is_some_and(|x| x.clear_env)
+    }
+
+    /// How long a single run of a task may take: the task's timeout, else the
+    /// execution timeout, else the default
+    pub fn timeout_for_task(&self, task: TaskType) -> Duration {
+        self.tasks
+            .get(&task)
+            .and_then(|task_config| task_config.timeout)
+            .or_else(|| self.execution.as_ref().and_then(|x| x.timeout))
+            .unwrap_or(DEFAULT_TIMEOUT)
+    }
+
+    /// Get the game versions supported for a task, if limited
+    pub fn supported_versions_for_task(&self, task: TaskType, game: Game) -> Option<&VersionRange> {
+        self.tasks
+            .get(&task)
+            .and_then(|task_config| task_config.versions.get(&game))
+    }
 }
 
 #[cfg(test)]
@@ -185,6 +503,9 @@ mod tests {
             
             [tasks.deserialization]
             games = ["vic3", "stellaris"]
+
+            [tasks.localisation]
+            games = ["ck3"]
         "#;
 
         let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
@@ -201,6 +522,285 @@ mod tests {
         assert_eq!(deser_games.len(), 2);
         assert!(deser_games.contains(&Game::Vic3));
         assert!(deser_games.contains(&Game::Stellaris));
+
+        // Test localisation task games
+        let localisation_games = config.supported_games_for_task(TaskType::Localisation);
+        assert_eq!(localisation_games, vec![Game::Ck3]);
+    }
+
+    #[test]
+    fn test_task_config_with_versions() {
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [tasks.can-parse]
+            games = ["eu4", "ck3"]
+            versions = { ck3 = "..1.8" }
+        "#;
+
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        let version = |s| GameVersion::from_str(s).unwrap();
+
+        assert!(config.supports_version_for_task(TaskType::CanParse, Game::Ck3, &version("1.8")));
+        assert!(!config.supports_version_for_task(TaskType::CanParse, Game::Ck3, &version("1.9")));
+        assert!(config.supports_version_for_task(TaskType::CanParse, Game::Eu4, &version("1.37")));
+
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [tasks.can-parse]
+            games = ["ck3"]
+            versions = { ck3 = "newest" }
+        "#;
+        assert!(toml::from_str::<ImplementationConfig>(toml_config).is_err());
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [execution]
+            timeout = 10
+
+            [tasks.can-parse]
+            games = ["eu4"]
+            timeout = 0.5
+
+            [tasks.deserialization]
+            games = ["eu4"]
+        "#;
+
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert_eq!(
+            config.timeout_for_task(TaskType::CanParse),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            config.timeout_for_task(TaskType::Deserialization),
+            Duration::from_secs(10)
+        );
+        assert_eq!(
+            config.timeout_for_task(TaskType::Localisation),
+            Duration::from_secs(10)
+        );
+
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [tasks.can-parse]
+            games = ["eu4"]
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert_eq!(config.timeout_for_task(TaskType::CanParse), DEFAULT_TIMEOUT);
+
+        for timeout in ["0", "-1"] {
+            let toml_config = format!(
+                "name = \"test-impl\"\nproject-type = \"rust\"\n[execution]\ntimeout = {}\n[tasks]",
+                timeout
+            );
+            assert!(toml::from_str::<ImplementationConfig>(&toml_config).is_err());
+        }
+    }
+
+    #[test]
+    fn test_max_output_kb() {
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [execution]
+            max-output-kb = 2
+
+            [tasks]
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert_eq!(config.max_output_bytes(), 2048);
+
+        let toml_config = toml_config.replace("max-output-kb = 2", "max-output-kb = 0");
+        assert!(toml::from_str::<ImplementationConfig>(&toml_config).is_err());
+    }
+
+    #[test]
+    fn test_protocol() {
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "nodejs"
+
+            [execution]
+            protocol = "server"
+
+            [tasks]
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert_eq!(config.protocol(), Protocol::Server);
+
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "nodejs"
+
+            [execution]
+            build-command = "npm ci"
+
+            [tasks]
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert_eq!(config.protocol(), Protocol::Process);
+    }
+
+    #[test]
+    fn test_protocol_version() {
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+            protocol-version = 2
+
+            [tasks]
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert_eq!(config.protocol_version, ProtocolVersion::V2);
+
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [tasks]
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert_eq!(config.protocol_version, ProtocolVersion::V1);
+
+        for version in ["3", "0", "\"2\""] {
+            let toml_config = format!(
+                "name = \"test-impl\"\nproject-type = \"rust\"\nprotocol-version = {}\n[tasks]",
+                version
+            );
+            assert!(toml::from_str::<ImplementationConfig>(&toml_config).is_err());
+        }
+    }
+
+    #[test]
+    fn test_input_mode() {
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [tasks.can-parse]
+            games = ["eu4"]
+
+            [tasks.deserialization]
+            games = ["eu4"]
+            input = "file"
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert_eq!(config.input_for_task(TaskType::CanParse), InputMode::Stdin);
+        assert_eq!(
+            config.input_for_task(TaskType::Deserialization),
+            InputMode::File
+        );
+        assert_eq!(
+            config.input_for_task(TaskType::Localisation),
+            InputMode::Stdin
+        );
+
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [tasks.can-parse]
+            games = ["eu4"]
+            input = "mmap"
+        "#;
+        assert!(toml::from_str::<ImplementationConfig>(toml_config).is_err());
+    }
+
+    #[test]
+    fn test_environment() {
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "nodejs"
+
+            [execution]
+            working-dir = "js"
+            clear-env = true
+            env = { NODE_OPTIONS = "--max-old-space-size=4096", NODE_ENV = "production" }
+
+            [tasks.can-parse]
+            games = ["eu4"]
+            working-dir = "js/can-parse"
+            env = { NODE_ENV = "test" }
+
+            [tasks.deserialization]
+            games = ["eu4"]
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert!(config.clear_env());
+
+        // Task variables are applied last, so they win
+        let env = config.env_for_task(Some(TaskType::CanParse));
+        assert_eq!(env.len(), 3);
+        assert_eq!(env.last(), Some(&("NODE_ENV", "test")));
+        assert_eq!(config.env_for_task(None).len(), 2);
+        assert_eq!(
+            config.env_for_task(Some(TaskType::Deserialization)).len(),
+            2
+        );
+
+        assert_eq!(
+            config.working_dir_for_task(Some(TaskType::CanParse)),
+            Some(Path::new("js/can-parse"))
+        );
+        assert_eq!(
+            config.working_dir_for_task(Some(TaskType::Deserialization)),
+            Some(Path::new("js"))
+        );
+        assert_eq!(config.working_dir_for_task(None), Some(Path::new("js")));
+
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [tasks]
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert!(!config.clear_env());
+        assert!(config.env_for_task(Some(TaskType::CanParse)).is_empty());
+        assert_eq!(config.working_dir_for_task(Some(TaskType::CanParse)), None);
+    }
+
+    #[test]
+    fn test_limits() {
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [execution.limits]
+            memory-mb = 512
+            processes = 64
+
+            [tasks]
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert_eq!(
+            config.limits(),
+            ResourceLimits {
+                memory_mb: Some(512),
+                processes: Some(64),
+                ..Default::default()
+            }
+        );
+
+        let toml_config = r#"
+            name = "test-impl"
+            project-type = "rust"
+
+            [tasks]
+        "#;
+        let config: ImplementationConfig = toml::from_str(toml_config).unwrap();
+        assert_eq!(config.limits(), ResourceLimits::default());
     }
 
     #[test]
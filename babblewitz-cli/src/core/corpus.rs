@@ -1,10 +1,13 @@
+use crate::core::config::TaskType;
 use crate::core::savefile::Game;
+use crate::core::version::GameVersion;
 use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 /// Expand game aliases like "all" to their full game lists
-fn expand_game_aliases(games_part: &str) -> Result<Vec<Game>> {
+pub(crate) fn expand_game_aliases(games_part: &str) -> Result<Vec<Game>> {
     let mut expanded_games = Vec::new();
 
     for token in games_part.split_whitespace() {
@@ -41,94 +44,838 @@ fn expand_game_aliases(games_part: &str) -> Result<Vec<Game>> {
     Ok(unique_games)
 }
 
+/// Suffix of the sidecar file holding expected task results for a corpus file
+const EXPECTED_SUFFIX: &str = ".expected.json";
+
+/// A test case from the corpus: either a whole corpus file or one of the
+/// cases it is split into
 #[derive(Debug, Clone)]
 pub struct CorpusFile {
     pub path: PathBuf,
+    /// Name of the case within the file, if the file is split into cases
+    pub case: Option<String>,
+    /// 1-based line where the test case begins
+    pub line: usize,
+    /// 1-based line where the content sent to implementations begins
+    pub content_line: usize,
     pub games: Vec<Game>,
+    pub expect: ExpectDirective,
+    pub encoding: Option<Encoding>,
+    pub since: VersionConstraints,
+    pub metadata: Metadata,
     pub content: Vec<u8>,
+    pub expected: Expectations,
+}
+
+impl CorpusFile {
+    /// Display name of the test case (eg: `013-keys.txt` or `033-probes.txt#hsv`)
+    pub fn name(&self) -> String {
+        let file_name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        match &self.case {
+            Some(case) => format!("{}#{}", file_name, case),
+            None => file_name.to_string(),
+        }
+    }
+}
+
+/// Expected task results loaded from a `<name>.expected.json` sidecar, keyed
+/// by task name:
+///
+/// ```json
+/// { "can-parse": { "flavor_tur": { "8": "yes" } } }
+/// ```
+///
+/// Files split into cases key the sidecar by case name first:
+///
+/// ```json
+/// { "quoted": { "can-parse": "3" } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Expectations(HashMap<TaskType, serde_json::Value>);
+
+impl Expectations {
+    /// Expected result for the given task, if one was declared
+    pub fn for_task(&self, task: TaskType) -> Option<&serde_json::Value> {
+        self.0.get(&task)
+    }
+
+    /// Check a task's reported result against the expectation. Tasks without
+    /// an expectation always pass.
+    ///
+    /// String expectations are compared against the trimmed output verbatim,
+    /// while any other JSON value requires the output to be JSON that is
+    /// structurally equal.
+    pub fn check(&self, task: TaskType, output: &str) -> Result<(), String> {
+        let Some(expected) = self.for_task(task) else {
+            return Ok(());
+        };
+
+        let output = output.trim();
+        let matches = match expected {
+            serde_json::Value::String(expected) => expected == output,
+            expected => serde_json::from_str::<serde_json::Value>(output)
+                .is_ok_and(|actual| &actual == expected),
+        };
+
+        if matches {
+            Ok(())
+        } else {
+            Err(format!("Expected result {} but got: {}", expected, output))
+        }
+    }
+}
+
+/// Path of the expectation sidecar for a corpus file (`013-keys.txt` ->
+/// `013-keys.expected.json`)
+pub(crate) fn expected_path(file_path: &Path) -> PathBuf {
+    let stem = file_path.file_stem().unwrap_or_default().to_string_lossy();
+    file_path.with_file_name(format!("{}{}", stem, EXPECTED_SUFFIX))
+}
+
+/// Load the expectation sidecar for a corpus file, if present, with an entry
+/// for each of the file's cases
+fn load_expectations(file_path: &Path, cases: &[Option<String>]) -> Result<Vec<Expectations>> {
+    let path = expected_path(file_path);
+    if !path.exists() {
+        return Ok(vec![Expectations::default(); cases.len()]);
+    }
+
+    let parse = || -> Result<Vec<Expectations>> {
+        let content = std::fs::read_to_string(&path)?;
+        let value: serde_json::Value = serde_json::from_str(&content)?;
+        if let [None] = cases {
+            return Ok(vec![serde_json::from_value(value)?]);
+        }
+
+        let mut by_case: HashMap<String, Expectations> = serde_json::from_value(value)?;
+        let expectations = cases
+            .iter()
+            .map(|case| {
+                case.as_ref()
+                    .and_then(|case| by_case.remove(case))
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        if let Some(case) = by_case.keys().next() {
+            anyhow::bail!("Expectations for unknown case: '{}'", case);
+        }
+
+        Ok(expectations)
+    };
+
+    parse().with_context(|| format!("Failed to parse expectations {}", path.display()))
+}
+
+/// Whether a path is an expectation sidecar rather than a corpus input
+pub(crate) fn is_expectation_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(EXPECTED_SUFFIX))
+}
+
+/// Whether an implementation is expected to accept or reject a corpus input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Expect {
+    /// The input is well formed and must be parsed
+    #[default]
+    Accept,
+    /// The input is malformed and must be rejected
+    Reject,
+    /// The input is malformed, but tolerating it is as valid as rejecting it
+    Either,
+}
+
+impl Expect {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "accept" => Some(Expect::Accept),
+            "reject" => Some(Expect::Reject),
+            "either" => Some(Expect::Either),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Expect::Accept => "accept",
+            Expect::Reject => "reject",
+            Expect::Either => "either",
+        }
+    }
+
+    /// Whether the parse outcome matches the expectation exactly
+    pub fn strict_pass(&self, accepted: bool) -> bool {
+        match self {
+            Expect::Accept => accepted,
+            Expect::Reject => !accepted,
+            Expect::Either => true,
+        }
+    }
+
+    /// Whether the parse outcome is acceptable for a parser that recovers from
+    /// malformed input: only inputs expected to be accepted must be accepted
+    pub fn lenient_pass(&self, accepted: bool) -> bool {
+        match self {
+            Expect::Accept => accepted,
+            Expect::Reject | Expect::Either => true,
+        }
+    }
+}
+
+impl std::fmt::Display for Expect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The `expect` directive: a default expectation with optional per-game
+/// overrides (eg: `# @babblewitz:expect: accept ck3=reject`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExpectDirective {
+    pub default: Expect,
+    pub games: Vec<(Game, Expect)>,
 }
 
-/// Parse a corpus file directive from content bytes and extract games list and content
-pub fn parse_corpus_content(content_bytes: &[u8]) -> Result<(Vec<Game>, Vec<u8>)> {
-    // Try to parse as UTF-8 first for directive parsing
-    let content_str = String::from_utf8_lossy(content_bytes);
-    let lines: Vec<&str> = content_str.lines().collect();
-
-    if lines.is_empty() {
-        return Ok((vec![], content_bytes.to_vec()));
-    }
-
-    // Check if first line is a games directive
-    let first_line = lines[0].trim();
-    if first_line.starts_with("# @babblewitz:games:") {
-        // Parse the games list
-        let games_part = first_line
-            .strip_prefix("# @babblewitz:games:")
-            .unwrap_or("")
-            .trim();
-        let games: Vec<Game> = expand_game_aliases(games_part)?;
-
-        // Content is everything after the first line (as bytes)
-        let content_without_directive = if lines.len() > 1 {
-            // Find the position after the first newline in the original bytes
-            let mut split_pos = 0;
-            for (i, &byte) in content_bytes.iter().enumerate() {
-                if byte == b'\n' {
-                    split_pos = i + 1;
-                    break;
+impl ExpectDirective {
+    /// Parse the value of an `expect` directive
+    fn parse(value: &str) -> Result<Self> {
+        let parse_expect = |token: &str| {
+            Expect::from_str(token).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unrecognized expectation: '{}' (expected accept, reject, or either)",
+                    token
+                )
+            })
+        };
+
+        let mut default = None;
+        let mut games = Vec::new();
+        for token in value.split_whitespace() {
+            match token.split_once('=') {
+                Some((game, expect)) => {
+                    let game = Game::from_str(game)
+                        .ok_or_else(|| anyhow::anyhow!("Unrecognized game: '{}'", game))?;
+                    games.push((game, parse_expect(expect)?));
                 }
-                if byte == b'\r' {
-                    split_pos = i + 1;
-                    // Check for CRLF
-                    if i + 1 < content_bytes.len() && content_bytes[i + 1] == b'\n' {
-                        split_pos = i + 2;
-                    }
-                    break;
+                None => {
+                    anyhow::ensure!(
+                        default.is_none(),
+                        "Multiple default expectations in '{}'",
+                        value
+                    );
+                    default = Some(parse_expect(token)?);
                 }
             }
-            content_bytes[split_pos..].to_vec()
+        }
+
+        Ok(ExpectDirective {
+            default: default.unwrap_or_default(),
+            games,
+        })
+    }
+
+    /// The expectation for a specific game
+    pub fn for_game(&self, game: Game) -> Expect {
+        self.games
+            .iter()
+            .rev()
+            .find(|(x, _)| *x == game)
+            .map(|(_, expect)| *expect)
+            .unwrap_or(self.default)
+    }
+}
+
+/// Byte encoding of a corpus input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8 without a byte order mark
+    Utf8,
+    /// UTF-8 prefixed with a byte order mark
+    Utf8Bom,
+    /// Windows-1252, as used by older games like EU4
+    Windows1252,
+}
+
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+impl Encoding {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "utf-8" => Some(Encoding::Utf8),
+            "utf-8-bom" => Some(Encoding::Utf8Bom),
+            "windows-1252" => Some(Encoding::Windows1252),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf-8",
+            Encoding::Utf8Bom => "utf-8-bom",
+            Encoding::Windows1252 => "windows-1252",
+        }
+    }
+
+    /// Verify that content is plausibly in this encoding. Windows-1252 can
+    /// decode any byte sequence, so it is only rejected when the content
+    /// would be better described as ASCII or UTF-8.
+    pub fn validate(&self, content: &[u8]) -> Result<()> {
+        match self {
+            Encoding::Utf8 => {
+                std::str::from_utf8(content)
+                    .map_err(|e| anyhow::anyhow!("Declared utf-8 but content is not: {}", e))?;
+                anyhow::ensure!(
+                    !content.starts_with(UTF8_BOM),
+                    "Declared utf-8 but content starts with a byte order mark (use utf-8-bom)"
+                );
+            }
+            Encoding::Utf8Bom => {
+                let body = content.strip_prefix(UTF8_BOM).ok_or_else(|| {
+                    anyhow::anyhow!("Declared utf-8-bom but content lacks a byte order mark")
+                })?;
+                std::str::from_utf8(body).map_err(|e| {
+                    anyhow::anyhow!("Declared utf-8-bom but content is not utf-8: {}", e)
+                })?;
+            }
+            Encoding::Windows1252 => {
+                anyhow::ensure!(
+                    !content.is_ascii(),
+                    "Declared windows-1252 but content is ASCII only"
+                );
+                anyhow::ensure!(
+                    std::str::from_utf8(content).is_err(),
+                    "Declared windows-1252 but content is valid utf-8"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Descriptive directives that don't affect how a corpus file is executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MetadataKey {
+    /// Human readable summary of what the input covers
+    Description,
+    /// Whitespace separated labels used to categorize inputs
+    Tags,
+    /// Game version the input was observed in (eg: `eu4 1.37.2`)
+    Source,
+    /// Link to an issue related to the input, may be repeated
+    Issue,
+}
+
+impl MetadataKey {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "description" => Some(MetadataKey::Description),
+            "tags" => Some(MetadataKey::Tags),
+            "source" => Some(MetadataKey::Source),
+            "issue" => Some(MetadataKey::Issue),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetadataKey::Description => "description",
+            MetadataKey::Tags => "tags",
+            MetadataKey::Source => "source",
+            MetadataKey::Issue => "issue",
+        }
+    }
+
+    /// Whether the key accumulates values across repeated directives
+    fn is_list(&self) -> bool {
+        matches!(self, MetadataKey::Tags | MetadataKey::Issue)
+    }
+}
+
+impl std::fmt::Display for MetadataKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+/// Metadata declared in the header block of a corpus file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata(BTreeMap<MetadataKey, MetadataValue>);
+
+impl Metadata {
+    fn insert(&mut self, key: MetadataKey, value: &str) -> Result<()> {
+        if key.is_list() {
+            let items = value.split_whitespace().map(String::from);
+            match self
+                .0
+                .entry(key)
+                .or_insert_with(|| MetadataValue::List(Vec::new()))
+            {
+                MetadataValue::List(list) => list.extend(items),
+                MetadataValue::Text(_) => unreachable!("list keys only hold lists"),
+            }
         } else {
-            Vec::new()
+            anyhow::ensure!(!self.0.contains_key(&key), "Duplicate directive: '{}'", key);
+            self.0.insert(key, MetadataValue::Text(value.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Combine with the metadata declared by a case: text values are
+    /// overridden while lists are extended
+    fn merged(&self, case: Metadata) -> Metadata {
+        let mut merged = self.clone();
+        for (key, value) in case.0 {
+            match (merged.0.get_mut(&key), value) {
+                (Some(MetadataValue::List(list)), MetadataValue::List(items)) => list.extend(items),
+                (_, value) => {
+                    merged.0.insert(key, value);
+                }
+            }
+        }
+        merged
+    }
+
+    pub fn get(&self, key: MetadataKey) -> Option<&MetadataValue> {
+        self.0.get(&key)
+    }
+
+    fn text(&self, key: MetadataKey) -> Option<&str> {
+        match self.get(key) {
+            Some(MetadataValue::Text(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    fn list(&self, key: MetadataKey) -> &[String] {
+        match self.get(key) {
+            Some(MetadataValue::List(list)) => list,
+            _ => &[],
+        }
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.text(MetadataKey::Description)
+    }
+
+    pub fn tags(&self) -> &[String] {
+        self.list(MetadataKey::Tags)
+    }
+
+    pub fn issues(&self) -> &[String] {
+        self.list(MetadataKey::Issue)
+    }
+}
+
+/// The earliest game versions an input requires, parsed from a directive like
+/// `since: ck3@1.9 vic3@1.3`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VersionConstraints(Vec<(Game, GameVersion)>);
+
+impl VersionConstraints {
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut constraints = Vec::new();
+        for token in value.split_whitespace() {
+            let (game, version) = token.split_once('@').ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Malformed version constraint: '{}' (expected game@version)",
+                    token
+                )
+            })?;
+            let game = Game::from_str(game)
+                .ok_or_else(|| anyhow::anyhow!("Unrecognized game: '{}'", game))?;
+            let version = GameVersion::from_str(version)
+                .ok_or_else(|| anyhow::anyhow!("Unrecognized version: '{}'", version))?;
+            anyhow::ensure!(
+                constraints.iter().all(|(x, _)| *x != game),
+                "Multiple version constraints for: {}",
+                game
+            );
+            constraints.push((game, version));
+        }
+
+        anyhow::ensure!(!constraints.is_empty(), "No version constraints listed");
+        Ok(VersionConstraints(constraints))
+    }
+
+    /// The earliest version of a game the input requires, if constrained
+    pub fn for_game(&self, game: Game) -> Option<&GameVersion> {
+        self.0.iter().find(|(x, _)| *x == game).map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Game, GameVersion)> {
+        self.0.iter()
+    }
+}
+
+/// Directives declared in the header block at the top of a corpus file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorpusHeader {
+    pub games: Vec<Game>,
+    pub expect: ExpectDirective,
+    pub encoding: Option<Encoding>,
+    pub since: VersionConstraints,
+    pub metadata: Metadata,
+}
+
+const DIRECTIVE_PREFIX: &str = "# @babblewitz:";
+
+/// Split off the first line (including its terminator) from the rest of the bytes
+fn split_first_line(content_bytes: &[u8]) -> (&[u8], &[u8]) {
+    for (i, &byte) in content_bytes.iter().enumerate() {
+        if byte == b'\n' {
+            return (&content_bytes[..i], &content_bytes[i + 1..]);
+        }
+        if byte == b'\r' {
+            // Check for CRLF
+            let end = if content_bytes.get(i + 1) == Some(&b'\n') {
+                i + 2
+            } else {
+                i + 1
+            };
+            return (&content_bytes[..i], &content_bytes[end..]);
+        }
+    }
+
+    (content_bytes, &[])
+}
+
+/// Parse the header block of `# @babblewitz:<key>: <value>` directive lines
+/// from content bytes and extract the header and the remaining content. The
+/// header ends at the first line that isn't a directive.
+pub fn parse_corpus_content(content_bytes: &[u8]) -> Result<(CorpusHeader, Vec<u8>)> {
+    let (directives, content) = header_directives(content_bytes);
+    let header = parse_header(&directives, &CorpusHeader::default(), 1, content)?;
+    Ok((header, content.to_vec()))
+}
+
+/// A test case split out of corpus file content
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusCase {
+    /// Name from the case directive, absent if the file isn't split into cases
+    pub name: Option<String>,
+    /// 1-based line where the case begins
+    pub line: usize,
+    /// 1-based line where the case's content begins
+    pub content_line: usize,
+    pub header: CorpusHeader,
+    pub content: Vec<u8>,
+}
+
+const CASE_DIRECTIVE: &str = "case";
+
+/// Parse corpus file content into its test cases.
+///
+/// A `# @babblewitz:case: <name>` line starts a new case that runs until the
+/// next case line. Directives immediately following the case line apply to
+/// that case only, overriding the file's header block. Files without case
+/// lines are a single unnamed case.
+pub fn parse_corpus_cases(content_bytes: &[u8]) -> Result<Vec<CorpusCase>> {
+    let (directives, rest) = header_directives(content_bytes);
+    let content_line = directives.len() + 1;
+
+    let case_starts = find_case_lines(rest, content_line);
+    let Some(&(first_case, _)) = case_starts.first() else {
+        let (header, content) = parse_corpus_content(content_bytes)?;
+        return Ok(vec![CorpusCase {
+            name: None,
+            line: 1,
+            content_line,
+            header,
+            content,
+        }]);
+    };
+
+    // The file header only provides defaults, so there's no content to validate
+    let file_header = parse_header(&directives, &CorpusHeader::default(), 1, &[])?;
+
+    if !rest[..first_case].trim_ascii().is_empty() {
+        return Err(DirectiveError {
+            line: content_line,
+            message: String::from("Content before the first case"),
+        }
+        .into());
+    }
+
+    let mut names = HashSet::new();
+    let mut cases = Vec::new();
+    for (i, &(start, line)) in case_starts.iter().enumerate() {
+        let end = case_starts.get(i + 1).map_or(rest.len(), |&(end, _)| end);
+        let (case_line, body) = split_first_line(&rest[start..end]);
+
+        let name = String::from_utf8_lossy(case_line)
+            .trim()
+            .strip_prefix(DIRECTIVE_PREFIX)
+            .and_then(|directive| directive.split_once(':'))
+            .map(|(_, name)| name.trim().to_string())
+            .unwrap_or_default();
+        let invalid_name = if name.is_empty() {
+            Some(String::from("Missing case name"))
+        } else if !names.insert(name.clone()) {
+            Some(format!("Duplicate case: '{}'", name))
+        } else {
+            None
+        };
+        if let Some(message) = invalid_name {
+            return Err(DirectiveError { line, message }.into());
+        }
+
+        let (directives, content) = header_directives(body);
+        let directives: Vec<_> = directives
+            .into_iter()
+            .map(|(offset, directive)| (line + offset, directive))
+            .collect();
+        let header = parse_header(&directives, &file_header, line, content)?;
+
+        cases.push(CorpusCase {
+            name: Some(name),
+            line,
+            content_line: line + directives.len() + 1,
+            header,
+            content: content.to_vec(),
+        });
+    }
+
+    Ok(cases)
+}
+
+/// Byte offsets and 1-based line numbers of case directive lines
+fn find_case_lines(content_bytes: &[u8], first_line: usize) -> Vec<(usize, usize)> {
+    let mut starts = Vec::new();
+    let mut rest = content_bytes;
+    let mut line_number = first_line;
+
+    while !rest.is_empty() {
+        let offset = content_bytes.len() - rest.len();
+        let (line, remainder) = split_first_line(rest);
+        if is_case_directive(&String::from_utf8_lossy(line)) {
+            starts.push((offset, line_number));
+        }
+
+        rest = remainder;
+        line_number += 1;
+    }
+
+    starts
+}
+
+fn is_case_directive(line: &str) -> bool {
+    line.trim()
+        .strip_prefix(DIRECTIVE_PREFIX)
+        .and_then(|directive| directive.split_once(':'))
+        .is_some_and(|(key, _)| key.trim() == CASE_DIRECTIVE)
+}
+
+/// Parse directives into a header that inherits anything undeclared from the
+/// base header, validating the content against the resulting encoding.
+/// `line` is where the header begins.
+fn parse_header(
+    directives: &[(usize, String)],
+    base: &CorpusHeader,
+    line: usize,
+    content: &[u8],
+) -> Result<CorpusHeader> {
+    let mut header = CorpusHeader {
+        metadata: Metadata::default(),
+        ..base.clone()
+    };
+    let mut seen = HashSet::new();
+    let mut encoding_line = line;
+
+    for (line, directive) in directives {
+        parse_directive(directive, &mut header, &mut seen).map_err(|e| DirectiveError {
+            line: *line,
+            message: e.to_string(),
+        })?;
+
+        if directive
+            .split_once(':')
+            .is_some_and(|(key, _)| key.trim() == "encoding")
+        {
+            encoding_line = *line;
+        }
+    }
+    header.metadata = base.metadata.merged(header.metadata);
+
+    if let Some(encoding) = header.encoding {
+        if !content.is_empty() {
+            encoding.validate(content).map_err(|e| DirectiveError {
+                line: encoding_line,
+                message: e.to_string(),
+            })?;
+        }
+    }
+
+    Ok(header)
+}
+
+/// Split the header block into its directives (sans prefix) paired with their
+/// 1-based line numbers, and the content that follows. A case directive ends
+/// the header block.
+pub(crate) fn header_directives(content_bytes: &[u8]) -> (Vec<(usize, String)>, &[u8]) {
+    let mut directives = Vec::new();
+    let mut rest = content_bytes;
+
+    while !rest.is_empty() {
+        let (line, remainder) = split_first_line(rest);
+
+        // Directives are ASCII, so lossy decoding is fine for detecting them
+        let line = String::from_utf8_lossy(line);
+        if is_case_directive(&line) {
+            break;
+        }
+
+        let Some(directive) = line.trim().strip_prefix(DIRECTIVE_PREFIX) else {
+            break;
         };
 
-        Ok((games, content_without_directive))
-    } else {
-        // No directive found, return empty games list and full content
-        Ok((vec![], content_bytes.to_vec()))
+        directives.push((directives.len() + 1, directive.to_string()));
+        rest = remainder;
+    }
+
+    (directives, rest)
+}
+
+/// An invalid directive in a corpus file header
+#[derive(Debug)]
+pub struct DirectiveError {
+    /// 1-based line number of the offending directive
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for DirectiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (line {})", self.message, self.line)
     }
 }
 
-/// Parse a corpus file directive and extract games list and content
-pub fn parse_corpus_file(file_path: &Path) -> Result<CorpusFile> {
+impl std::error::Error for DirectiveError {}
+
+/// Parse a single directive (sans prefix) into the header
+fn parse_directive(
+    directive: &str,
+    header: &mut CorpusHeader,
+    seen: &mut HashSet<String>,
+) -> Result<()> {
+    let (key, value) = directive
+        .split_once(':')
+        .ok_or_else(|| anyhow::anyhow!("Malformed directive: '{}'", directive))?;
+    let (key, value) = (key.trim(), value.trim());
+
+    match key {
+        "games" | "expect" | "encoding" | "since" => {
+            anyhow::ensure!(
+                seen.insert(key.to_string()),
+                "Duplicate directive: '{}'",
+                key
+            );
+            match key {
+                "games" => {
+                    anyhow::ensure!(!value.is_empty(), "No games listed");
+                    header.games = expand_game_aliases(value)?;
+                }
+                "expect" => header.expect = ExpectDirective::parse(value)?,
+                "since" => header.since = VersionConstraints::parse(value)?,
+                _ => {
+                    let encoding = Encoding::from_str(value).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unrecognized encoding: '{}' (expected utf-8, utf-8-bom, or windows-1252)",
+                            value
+                        )
+                    })?;
+                    header.encoding = Some(encoding);
+                }
+            }
+        }
+        key => {
+            let key = MetadataKey::from_str(key)
+                .ok_or_else(|| anyhow::anyhow!("Unrecognized directive: '{}'", key))?;
+            header.metadata.insert(key, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a corpus file into its test cases
+pub fn parse_corpus_file(file_path: &Path) -> Result<Vec<CorpusFile>> {
     let content_bytes = std::fs::read(file_path)?;
-    let (games, content) = parse_corpus_content(&content_bytes)?;
-    Ok(CorpusFile {
-        path: file_path.to_path_buf(),
-        games,
-        content,
-    })
+    let cases = parse_corpus_cases(&content_bytes)?;
+    let names: Vec<_> = cases.iter().map(|case| case.name.clone()).collect();
+    let expectations = load_expectations(file_path, &names)?;
+
+    let corpus_files = cases
+        .into_iter()
+        .zip(expectations)
+        .map(|(case, expected)| CorpusFile {
+            path: file_path.to_path_buf(),
+            case: case.name,
+            line: case.line,
+            content_line: case.content_line,
+            games: case.header.games,
+            expect: case.header.expect,
+            since: case.header.since,
+            encoding: case.header.encoding,
+            metadata: case.header.metadata,
+            content: case.content,
+            expected,
+        })
+        .collect();
+
+    Ok(corpus_files)
 }
 
-/// Collect corpus files relevant to the specified games
-pub fn collect_relevant_corpus_files(games_to_test: &[Game]) -> Result<Vec<CorpusFile>> {
-    let corpus_dir = PathBuf::from("corpus").join("game");
+/// Directory of the corpus exercised by a task
+pub fn task_corpus_dir(task: TaskType) -> PathBuf {
+    let dir = match task {
+        TaskType::CanParse => "game",
+        TaskType::Deserialization => "saves",
+        TaskType::Localisation => "localisation",
+    };
+    PathBuf::from("corpus").join(dir)
+}
+
+/// Collect a task's corpus files relevant to the specified games
+pub fn collect_relevant_corpus_files(
+    task: TaskType,
+    games_to_test: &[Game],
+) -> Result<Vec<CorpusFile>> {
+    let corpus_dir = task_corpus_dir(task);
     let mut all_corpus_files = Vec::new();
 
-    // Walk through all files in corpus/game directory
-    for entry in WalkDir::new(&corpus_dir).into_iter().filter_map(|e| e.ok()) {
-        if entry.file_type().is_file() {
-            let corpus_file = parse_corpus_file(entry.path()).with_context(|| {
+    // Walk through all files in the task's corpus directory
+    for entry in WalkDir::new(&corpus_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if entry.file_type().is_file() && !is_expectation_file(entry.path()) {
+            let corpus_files = parse_corpus_file(entry.path()).with_context(|| {
                 format!("Failed to parse corpus file {}", entry.path().display())
             })?;
 
-            // Only include files that are relevant to our games
-            if corpus_file
-                .games
-                .iter()
-                .any(|game| games_to_test.contains(game))
-            {
-                all_corpus_files.push(corpus_file);
-            }
+            // Only include cases that are relevant to our games
+            all_corpus_files.extend(corpus_files.into_iter().filter(|corpus_file| {
+                corpus_file
+                    .games
+                    .iter()
+                    .any(|game| games_to_test.contains(game))
+            }));
         }
     }
 
@@ -217,19 +964,19 @@ mod tests {
     #[test]
     fn test_parse_corpus_content_with_directive() {
         let content = b"# @babblewitz:games: eu4 ck3\ndate=1444.11.11\nplayer=\"FRA\"";
-        let (games, parsed_content) = parse_corpus_content(content).unwrap();
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
 
-        assert_eq!(games, vec![Game::Eu4, Game::Ck3]);
+        assert_eq!(header.games, vec![Game::Eu4, Game::Ck3]);
         assert_eq!(parsed_content, b"date=1444.11.11\nplayer=\"FRA\"");
     }
 
     #[test]
     fn test_parse_corpus_content_with_all_alias() {
         let content = b"# @babblewitz:games: all\ndate=1444.11.11\nplayer=\"FRA\"";
-        let (games, parsed_content) = parse_corpus_content(content).unwrap();
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
 
         assert_eq!(
-            games,
+            header.games,
             vec![
                 Game::Eu4,
                 Game::Ck3,
@@ -245,36 +992,36 @@ mod tests {
     #[test]
     fn test_parse_corpus_content_without_directive() {
         let content = b"date=1444.11.11\nplayer=\"FRA\"";
-        let (games, parsed_content) = parse_corpus_content(content).unwrap();
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
 
-        assert!(games.is_empty());
+        assert!(header.games.is_empty());
         assert_eq!(parsed_content, content);
     }
 
     #[test]
     fn test_parse_corpus_content_only_directive() {
         let content = b"# @babblewitz:games: eu4";
-        let (games, parsed_content) = parse_corpus_content(content).unwrap();
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
 
-        assert_eq!(games, vec![Game::Eu4]);
+        assert_eq!(header.games, vec![Game::Eu4]);
         assert_eq!(parsed_content, b"");
     }
 
     #[test]
     fn test_parse_corpus_content_empty() {
         let content = b"";
-        let (games, parsed_content) = parse_corpus_content(content).unwrap();
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
 
-        assert!(games.is_empty());
+        assert!(header.games.is_empty());
         assert_eq!(parsed_content, b"");
     }
 
     #[test]
     fn test_parse_corpus_content_old_format_ignored() {
         let content = b"# @games: eu4 ck3\ndate=1444.11.11";
-        let (games, parsed_content) = parse_corpus_content(content).unwrap();
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
 
-        assert!(games.is_empty());
+        assert!(header.games.is_empty());
         assert_eq!(parsed_content, content);
     }
 
@@ -284,8 +1031,8 @@ mod tests {
         let mut content = b"# @babblewitz:games: eu4\n".to_vec();
         content.extend_from_slice(b"name=\"M\xfcnchen\""); // ü in Windows-1252 is 0xfc
 
-        let (games, parsed_content) = parse_corpus_content(&content).unwrap();
-        assert_eq!(games, vec![Game::Eu4]);
+        let (header, parsed_content) = parse_corpus_content(&content).unwrap();
+        assert_eq!(header.games, vec![Game::Eu4]);
         assert_eq!(parsed_content, b"name=\"M\xfcnchen\"");
     }
 
@@ -301,9 +1048,9 @@ mod tests {
         content.extend_from_slice(b"country=\"Espa\xf1a\"\n");
         content.extend_from_slice(b"leader=\"M\xfcller\"\n");
 
-        let (games, parsed_content) = parse_corpus_content(&content).unwrap();
+        let (header, parsed_content) = parse_corpus_content(&content).unwrap();
         assert_eq!(
-            games,
+            header.games,
             vec![
                 Game::Eu4,
                 Game::Ck3,
@@ -323,9 +1070,9 @@ mod tests {
     fn test_parse_corpus_content_with_crlf() {
         // Test CRLF line ending handling
         let content = b"# @babblewitz:games: eu4 ck3\r\ndate=1444.11.11\r\nplayer=\"FRA\"";
-        let (games, parsed_content) = parse_corpus_content(content).unwrap();
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
 
-        assert_eq!(games, vec![Game::Eu4, Game::Ck3]);
+        assert_eq!(header.games, vec![Game::Eu4, Game::Ck3]);
         assert_eq!(parsed_content, b"date=1444.11.11\r\nplayer=\"FRA\"");
     }
 
@@ -341,4 +1088,448 @@ mod tests {
             .to_string()
             .contains("Unrecognized game: 'invalid_game'"));
     }
+
+    #[test]
+    fn test_parse_corpus_content_with_expect_directive() {
+        let content =
+            b"# @babblewitz:games: eu4 ck3\r\n# @babblewitz:expect: reject ck3=either\r\na = { b=c";
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
+
+        assert_eq!(header.games, vec![Game::Eu4, Game::Ck3]);
+        assert_eq!(header.expect.for_game(Game::Eu4), Expect::Reject);
+        assert_eq!(header.expect.for_game(Game::Ck3), Expect::Either);
+        assert_eq!(parsed_content, b"a = { b=c");
+    }
+
+    #[test]
+    fn test_parse_corpus_content_expect_defaults_to_accept() {
+        let content = b"# @babblewitz:games: eu4\na=b";
+        let (header, _) = parse_corpus_content(content).unwrap();
+        assert_eq!(header.expect.for_game(Game::Eu4), Expect::Accept);
+
+        let content = b"# @babblewitz:games: eu4 ck3\n# @babblewitz:expect: ck3=reject\na=b";
+        let (header, _) = parse_corpus_content(content).unwrap();
+        assert_eq!(header.expect.for_game(Game::Eu4), Expect::Accept);
+        assert_eq!(header.expect.for_game(Game::Ck3), Expect::Reject);
+    }
+
+    #[test]
+    fn test_parse_corpus_content_invalid_expect() {
+        let result = parse_corpus_content(b"# @babblewitz:expect: maybe\na=b");
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Unrecognized expectation: 'maybe'"));
+
+        let result = parse_corpus_content(b"# @babblewitz:expect: accept reject\na=b");
+        assert!(result.is_err());
+
+        let result = parse_corpus_content(b"# @babblewitz:expect: eu5=reject\na=b");
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Unrecognized game: 'eu5'"));
+    }
+
+    #[test]
+    fn test_parse_corpus_content_header_block() {
+        let content = b"# @babblewitz:games: eu4\n\
+# @babblewitz:description: Keys with unusual characters\n\
+# @babblewitz:tags: keys\n\
+# @babblewitz:tags: scalars quoted\n\
+# @babblewitz:source: eu4 1.37.2\n\
+# @babblewitz:issue: https://example.com/1\n\
+# @babblewitz:issue: https://example.com/2\n\
+flavor_tur.8=yes";
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
+
+        assert_eq!(header.games, vec![Game::Eu4]);
+        assert_eq!(
+            header.metadata.description(),
+            Some("Keys with unusual characters")
+        );
+        assert_eq!(
+            header.metadata.get(MetadataKey::Tags),
+            Some(&MetadataValue::List(vec![
+                String::from("keys"),
+                String::from("scalars"),
+                String::from("quoted")
+            ]))
+        );
+        assert_eq!(
+            header.metadata.get(MetadataKey::Source),
+            Some(&MetadataValue::Text(String::from("eu4 1.37.2")))
+        );
+        assert_eq!(
+            header.metadata.issues(),
+            &["https://example.com/1", "https://example.com/2"]
+        );
+        assert_eq!(parsed_content, b"flavor_tur.8=yes");
+    }
+
+    #[test]
+    fn test_parse_corpus_content_header_ends_at_first_content_line() {
+        let content = b"# @babblewitz:games: eu4\n# a comment\n# @babblewitz:tags: late\n";
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
+
+        assert!(header.metadata.get(MetadataKey::Tags).is_none());
+        assert_eq!(
+            parsed_content,
+            b"# a comment\n# @babblewitz:tags: late\n".as_slice()
+        );
+    }
+
+    #[test]
+    fn test_parse_corpus_content_header_preserves_windows_1252_crlf() {
+        let mut content =
+            b"# @babblewitz:games: eu4\r\n# @babblewitz:description: Windows-1252\r\n".to_vec();
+        content.extend_from_slice(b"name=\"J\xe5hk\xe5m\xe5hkke\"\r\n");
+
+        let (header, parsed_content) = parse_corpus_content(&content).unwrap();
+        assert_eq!(header.metadata.description(), Some("Windows-1252"));
+        assert_eq!(parsed_content, b"name=\"J\xe5hk\xe5m\xe5hkke\"\r\n");
+    }
+
+    #[test]
+    fn test_parse_corpus_content_unknown_directive() {
+        let content = b"# @babblewitz:games: eu4\n# @babblewitz:gmaes: ck3\na=b";
+        let error = format!("{:#}", parse_corpus_content(content).unwrap_err());
+        assert!(error.contains("(line 2)"));
+        assert!(error.contains("Unrecognized directive: 'gmaes'"));
+
+        let content = b"# @babblewitz:games eu4\na=b";
+        assert!(parse_corpus_content(content).is_err());
+    }
+
+    #[test]
+    fn test_parse_corpus_content_duplicate_directive() {
+        let content = b"# @babblewitz:games: eu4\n# @babblewitz:games: ck3\na=b";
+        let error = format!("{:#}", parse_corpus_content(content).unwrap_err());
+        assert!(error.contains("Duplicate directive: 'games'"));
+
+        let content = b"# @babblewitz:description: a\n# @babblewitz:description: b\na=b";
+        let error = format!("{:#}", parse_corpus_content(content).unwrap_err());
+        assert!(error.contains("Duplicate directive: 'description'"));
+    }
+
+    #[test]
+    fn test_parse_corpus_content_with_encoding() {
+        let content =
+            b"# @babblewitz:games: eu4\n# @babblewitz:encoding: windows-1252\nname=\"M\xfcnchen\"";
+        let (header, parsed_content) = parse_corpus_content(content).unwrap();
+        assert_eq!(header.encoding, Some(Encoding::Windows1252));
+        assert_eq!(parsed_content, b"name=\"M\xfcnchen\"");
+
+        let content =
+            "# @babblewitz:games: ck3\n# @babblewitz:encoding: utf-8\nname=\"M\u{fc}nchen\"";
+        let (header, _) = parse_corpus_content(content.as_bytes()).unwrap();
+        assert_eq!(header.encoding, Some(Encoding::Utf8));
+
+        let content =
+            b"# @babblewitz:games: ck3\n# @babblewitz:encoding: utf-8-bom\n\xef\xbb\xbfa=b";
+        let (header, _) = parse_corpus_content(content).unwrap();
+        assert_eq!(header.encoding, Some(Encoding::Utf8Bom));
+
+        let content = b"# @babblewitz:games: ck3\na=b";
+        let (header, _) = parse_corpus_content(content).unwrap();
+        assert_eq!(header.encoding, None);
+    }
+
+    #[test]
+    fn test_parse_corpus_content_encoding_mismatch() {
+        let error = |content: &[u8]| parse_corpus_content(content).unwrap_err().to_string();
+
+        assert!(error(b"# @babblewitz:encoding: utf-8\nname=\"M\xfcnchen\"")
+            .contains("Declared utf-8 but content is not"));
+        assert!(
+            error(b"# @babblewitz:encoding: utf-8\n\xef\xbb\xbfa=b").contains("byte order mark")
+        );
+        assert!(
+            error(b"# @babblewitz:encoding: utf-8-bom\na=b").contains("lacks a byte order mark")
+        );
+        assert!(
+            error(b"# @babblewitz:encoding: windows-1252\na=b").contains("content is ASCII only")
+        );
+        assert!(
+            error("# @babblewitz:encoding: windows-1252\nname=\"M\u{fc}nchen\"".as_bytes())
+                .contains("content is valid utf-8")
+        );
+        assert!(error(b"# @babblewitz:encoding: latin1\na=b")
+            .contains("Unrecognized encoding: 'latin1'"));
+    }
+
+    #[test]
+    fn test_parse_corpus_content_with_since() {
+        let content = b"# @babblewitz:games: ck3 vic3
+# @babblewitz:since: ck3@1.9 vic3@1.3.2
+a ?= b";
+        let (header, _) = parse_corpus_content(content).unwrap();
+        assert_eq!(
+            header.since.for_game(Game::Ck3),
+            GameVersion::from_str("1.9").as_ref()
+        );
+        assert_eq!(
+            header.since.for_game(Game::Vic3),
+            GameVersion::from_str("1.3.2").as_ref()
+        );
+        assert_eq!(header.since.for_game(Game::Eu4), None);
+
+        let error_of = |value: &str| {
+            let content = format!(
+                "# @babblewitz:since: {}
+a=b",
+                value
+            );
+            parse_corpus_content(content.as_bytes())
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error_of("ck3"),
+            "Malformed version constraint: 'ck3' (expected game@version) (line 1)"
+        );
+        assert_eq!(error_of("ck4@1.9"), "Unrecognized game: 'ck4' (line 1)");
+        assert_eq!(error_of("ck3@new"), "Unrecognized version: 'new' (line 1)");
+        assert_eq!(
+            error_of("ck3@1.9 ck3@1.10"),
+            "Multiple version constraints for: ck3 (line 1)"
+        );
+    }
+
+    #[test]
+    fn test_expect_scoring() {
+        assert!(Expect::Accept.strict_pass(true));
+        assert!(!Expect::Accept.strict_pass(false));
+        assert!(Expect::Accept.lenient_pass(true));
+        assert!(!Expect::Accept.lenient_pass(false));
+
+        assert!(!Expect::Reject.strict_pass(true));
+        assert!(Expect::Reject.strict_pass(false));
+        assert!(Expect::Reject.lenient_pass(true));
+        assert!(Expect::Reject.lenient_pass(false));
+
+        assert!(Expect::Either.strict_pass(true));
+        assert!(Expect::Either.strict_pass(false));
+        assert!(Expect::Either.lenient_pass(true));
+        assert!(Expect::Either.lenient_pass(false));
+    }
+
+    #[test]
+    fn test_parse_corpus_file_loads_expectations() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let corpus_path = temp_dir.path().join("013-keys.txt");
+        std::fs::write(&corpus_path, b"# @babblewitz:games: all\nflavor_tur.8=yes").unwrap();
+        std::fs::write(
+            temp_dir.path().join("013-keys.expected.json"),
+            r#"{ "can-parse": { "flavor_tur.8": "yes" } }"#,
+        )
+        .unwrap();
+
+        let corpus_files = parse_corpus_file(&corpus_path).unwrap();
+        let corpus_file = &corpus_files[0];
+        assert_eq!(corpus_file.name(), "013-keys.txt");
+        assert_eq!(
+            corpus_file.expected.for_task(TaskType::CanParse),
+            Some(&serde_json::json!({ "flavor_tur.8": "yes" }))
+        );
+        assert_eq!(
+            corpus_file.expected.for_task(TaskType::Deserialization),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_corpus_file_without_expectations() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let corpus_path = temp_dir.path().join("002-scalars.txt");
+        std::fs::write(&corpus_path, b"# @babblewitz:games: all\naaa=foo").unwrap();
+
+        let corpus_files = parse_corpus_file(&corpus_path).unwrap();
+        assert_eq!(corpus_files[0].expected, Expectations::default());
+    }
+
+    #[test]
+    fn test_parse_corpus_file_invalid_expectations() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let corpus_path = temp_dir.path().join("002-scalars.txt");
+        std::fs::write(&corpus_path, b"# @babblewitz:games: all\naaa=foo").unwrap();
+        std::fs::write(
+            temp_dir.path().join("002-scalars.expected.json"),
+            r#"{ "not-a-task": 1 }"#,
+        )
+        .unwrap();
+
+        assert!(parse_corpus_file(&corpus_path).is_err());
+    }
+
+    #[test]
+    fn test_parse_corpus_cases_single() {
+        let cases = parse_corpus_cases(b"# @babblewitz:games: eu4\naaa=foo").unwrap();
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].name, None);
+        assert_eq!(cases[0].line, 1);
+        assert_eq!(cases[0].content_line, 2);
+        assert_eq!(cases[0].header.games, vec![Game::Eu4]);
+        assert_eq!(cases[0].content, b"aaa=foo");
+    }
+
+    #[test]
+    fn test_parse_corpus_cases_inherit_header() {
+        let content = b"# @babblewitz:games: eu4 ck3\n\
+            # @babblewitz:description: Quoting\n\
+            # @babblewitz:tags: strings\n\
+            \n\
+            # @babblewitz:case: plain\n\
+            a=\"b\"\n\
+            # @babblewitz:case: unterminated\n\
+            # @babblewitz:games: ck3\n\
+            # @babblewitz:expect: reject\n\
+            # @babblewitz:description: Unterminated quote\n\
+            # @babblewitz:tags: malformed\n\
+            a=\"b\n";
+        let cases = parse_corpus_cases(content).unwrap();
+        assert_eq!(cases.len(), 2);
+
+        assert_eq!(cases[0].name.as_deref(), Some("plain"));
+        assert_eq!(cases[0].line, 5);
+        assert_eq!(cases[0].content_line, 6);
+        assert_eq!(cases[0].header.games, vec![Game::Eu4, Game::Ck3]);
+        assert_eq!(cases[0].header.expect, ExpectDirective::default());
+        assert_eq!(cases[0].header.metadata.description(), Some("Quoting"));
+        assert_eq!(cases[0].content, b"a=\"b\"\n");
+
+        assert_eq!(cases[1].name.as_deref(), Some("unterminated"));
+        assert_eq!(cases[1].line, 7);
+        assert_eq!(cases[1].content_line, 12);
+        assert_eq!(cases[1].header.games, vec![Game::Ck3]);
+        assert_eq!(cases[1].header.expect.default, Expect::Reject);
+        assert_eq!(
+            cases[1].header.metadata.description(),
+            Some("Unterminated quote")
+        );
+        assert_eq!(
+            cases[1].header.metadata.tags(),
+            &[String::from("strings"), String::from("malformed")]
+        );
+        assert_eq!(cases[1].content, b"a=\"b\n");
+    }
+
+    #[test]
+    fn test_parse_corpus_cases_errors() {
+        let error_of = |content: &[u8]| {
+            parse_corpus_cases(content)
+                .unwrap_err()
+                .downcast::<DirectiveError>()
+                .unwrap()
+        };
+
+        let error = error_of(b"# @babblewitz:games: all\na=b\n# @babblewitz:case: x\nc=d");
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "Content before the first case");
+
+        let error = error_of(b"# @babblewitz:games: all\n# @babblewitz:case:\na=b");
+        assert_eq!(error.line, 2);
+        assert_eq!(error.message, "Missing case name");
+
+        let error = error_of(
+            b"# @babblewitz:games: all\n# @babblewitz:case: x\na=b\n# @babblewitz:case: x\nc=d",
+        );
+        assert_eq!(error.line, 4);
+        assert_eq!(error.message, "Duplicate case: 'x'");
+
+        let error = error_of(
+            b"# @babblewitz:games: all\n# @babblewitz:case: x\n# @babblewitz:expect: nope\na=b",
+        );
+        assert_eq!(error.line, 3);
+    }
+
+    #[test]
+    fn test_parse_corpus_cases_encoding() {
+        // Each case is validated against the encoding it declares or inherits
+        let content = b"# @babblewitz:games: eu4\n\
+            # @babblewitz:encoding: windows-1252\n\
+            # @babblewitz:case: latin\n\
+            name=\"J\xe5hk\xe5m\xe5hkke\"\n\
+            # @babblewitz:case: unicode\n\
+            # @babblewitz:encoding: utf-8\n\
+            name=\"J\xc3\xa5hk\xc3\xa5m\xc3\xa5hkke\"\n";
+        let cases = parse_corpus_cases(content).unwrap();
+        assert_eq!(cases[0].header.encoding, Some(Encoding::Windows1252));
+        assert_eq!(cases[1].header.encoding, Some(Encoding::Utf8));
+
+        let content = b"# @babblewitz:games: eu4\n\
+            # @babblewitz:encoding: windows-1252\n\
+            # @babblewitz:case: ascii\n\
+            name=bob\n";
+        let error = parse_corpus_cases(content)
+            .unwrap_err()
+            .downcast::<DirectiveError>()
+            .unwrap();
+        assert_eq!(error.line, 3);
+    }
+
+    #[test]
+    fn test_parse_corpus_file_case_expectations() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let corpus_path = temp_dir.path().join("033-probes.txt");
+        std::fs::write(
+            &corpus_path,
+            b"# @babblewitz:games: all\n# @babblewitz:case: a\na=b\n# @babblewitz:case: b\nc=d",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("033-probes.expected.json"),
+            r#"{ "b": { "can-parse": "1" } }"#,
+        )
+        .unwrap();
+
+        let corpus_files = parse_corpus_file(&corpus_path).unwrap();
+        let names: Vec<_> = corpus_files.iter().map(|x| x.name()).collect();
+        assert_eq!(names, vec!["033-probes.txt#a", "033-probes.txt#b"]);
+        assert_eq!(corpus_files[0].expected, Expectations::default());
+        assert_eq!(
+            corpus_files[1].expected.for_task(TaskType::CanParse),
+            Some(&serde_json::json!("1"))
+        );
+
+        std::fs::write(
+            temp_dir.path().join("033-probes.expected.json"),
+            r#"{ "c": { "can-parse": "1" } }"#,
+        )
+        .unwrap();
+        assert!(parse_corpus_file(&corpus_path).is_err());
+    }
+
+    #[test]
+    fn test_is_expectation_file() {
+        assert!(is_expectation_file(Path::new(
+            "corpus/game/013-keys.expected.json"
+        )));
+        assert!(!is_expectation_file(Path::new("corpus/game/013-keys.txt")));
+    }
+
+    #[test]
+    fn test_expectations_check() {
+        let expectations: Expectations = serde_json::from_str(
+            r#"{ "can-parse": { "flavor_tur": { "8": "yes" } }, "deserialization": "7" }"#,
+        )
+        .unwrap();
+
+        // Structural comparison ignores formatting differences
+        assert!(expectations
+            .check(TaskType::CanParse, r#"{"flavor_tur":{"8":"yes"}}"#)
+            .is_ok());
+        assert!(expectations
+            .check(TaskType::CanParse, r#"{"flavor_tur.8":"yes"}"#)
+            .is_err());
+        assert!(expectations.check(TaskType::CanParse, "12").is_err());
+
+        // String expectations compare the trimmed output verbatim
+        assert!(expectations.check(TaskType::Deserialization, "7\n").is_ok());
+        assert!(expectations.check(TaskType::Deserialization, "8").is_err());
+
+        // Tasks without expectations always pass
+        let empty = Expectations::default();
+        assert!(empty.check(TaskType::CanParse, "anything").is_ok());
+    }
 }
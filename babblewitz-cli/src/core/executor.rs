@@ -1,8 +1,23 @@
-use crate::core::config::{ProjectType, TaskType};
+use crate::core::capture::{self, CappedBuffer};
+use crate::core::config::{
+    ImplementationConfig, InputMode, ProjectType, Protocol, ProtocolVersion, ResourceLimits,
+    TaskType,
+};
+use crate::core::corpus::Encoding;
 use crate::core::implementation::Implementation;
+use crate::core::isolation;
+use crate::core::limits::{self, Limit};
+use crate::core::output::{self, OutputErrorKind, ParsedOutput};
+use crate::core::rusage::{self, ResourceUsage};
 use crate::core::savefile::Game;
+use crate::core::server::{Reply, Request, Response, ServerProcess};
 use anyhow::Result;
-use std::process::{Command, Stdio};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::{mpsc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
 // Build state markers
 pub struct Initial;
@@ -10,19 +25,176 @@ pub struct Built;
 
 pub struct ImplementationExecutor<'a, Stage = Initial> {
     implementation: &'a Implementation,
+    /// The idle servers of each task when the implementation uses the server
+    /// protocol, as tasks may run with their own environment. A server is
+    /// started whenever every other is busy, so there is one per concurrent
+    /// job.
+    servers: Mutex<HashMap<TaskType, Vec<ServerProcess>>>,
+    /// CPU that runs are pinned to in isolation mode
+    cpu: Option<usize>,
     _stage: std::marker::PhantomData<Stage>,
 }
 
 #[derive(Debug)]
 pub enum ExecutionResult {
-    Success { elapsed: std::time::Duration },
-    Error { error: String },
+    Success {
+        /// Time the implementation reported for the task
+        elapsed: std::time::Duration,
+        /// Time measured from starting the process to its exit, or from
+        /// sending a server request to receiving its response
+        wall_time: Duration,
+        output: String,
+        /// What the implementation wrote to stderr. Servers share stderr
+        /// across inputs, so their runs have none.
+        stderr: String,
+        /// Resources used by the process, when the platform reports them.
+        /// Server runs share a process across inputs and don't have any.
+        usage: Option<ResourceUsage>,
+        /// Warnings reported alongside the result (protocol version 2)
+        warnings: Vec<String>,
+        /// Time reported for each phase of the task (protocol version 2)
+        phases: Vec<(String, Duration)>,
+    },
+    /// The implementation reported that the input is invalid
+    Rejected { reason: String },
+    /// The implementation was killed by a signal, typically from crashing
+    Crashed { signal: String, error: String },
+    /// The implementation exited with a nonzero code, or reported an error
+    /// for the input (which has no code)
+    NonzeroExit { code: Option<i32>, error: String },
+    /// The implementation's output didn't follow the protocol
+    MalformedOutput { error: String },
+    /// The implementation couldn't be started
+    SpawnFailed { error: String },
+    /// The implementation was stopped for exceeding a configured resource limit
+    LimitExceeded { limit: Limit, error: String },
+    /// The implementation didn't finish within the timeout and was killed
+    Timeout { timeout: Duration },
+}
+
+impl ExecutionResult {
+    pub fn outcome(&self) -> Outcome {
+        match self {
+            ExecutionResult::Success { .. } => Outcome::Success,
+            ExecutionResult::Rejected { .. } => Outcome::Rejected,
+            ExecutionResult::Crashed { .. } => Outcome::Crashed,
+            ExecutionResult::NonzeroExit { .. } => Outcome::NonzeroExit,
+            ExecutionResult::MalformedOutput { .. } => Outcome::MalformedOutput,
+            ExecutionResult::SpawnFailed { .. } => Outcome::SpawnFailed,
+            ExecutionResult::LimitExceeded { .. } => Outcome::LimitExceeded,
+            ExecutionResult::Timeout { .. } => Outcome::TimedOut,
+        }
+    }
+
+    /// Explain an unsuccessful result for failure reports
+    pub fn error_message(&self) -> Option<String> {
+        let message = match self {
+            ExecutionResult::Success { .. } => return None,
+            ExecutionResult::Rejected { reason } if reason.is_empty() => {
+                String::from("Rejected the input")
+            }
+            ExecutionResult::Rejected { reason } => format!("Rejected the input: {}", reason),
+            ExecutionResult::Crashed { signal, error } => {
+                format!("Crashed ({}): {}", signal, error)
+            }
+            ExecutionResult::NonzeroExit {
+                code: Some(code),
+                error,
+            } => format!("Exited with code {}: {}", code, error),
+            ExecutionResult::NonzeroExit { code: None, error } => {
+                format!("Reported an error: {}", error)
+            }
+            ExecutionResult::MalformedOutput { error } => format!("Malformed output: {}", error),
+            ExecutionResult::SpawnFailed { error } => format!("Failed to start: {}", error),
+            ExecutionResult::LimitExceeded { limit, error } => {
+                format!("{} limit exceeded: {}", limit.description(), error)
+            }
+            ExecutionResult::Timeout { timeout } => format!("Timed out after {:?}", timeout),
+        };
+        Some(message)
+    }
+}
+
+/// The category of an execution's result, for tallying results
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    Success,
+    Rejected,
+    Crashed,
+    NonzeroExit,
+    MalformedOutput,
+    SpawnFailed,
+    LimitExceeded,
+    TimedOut,
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 8] = [
+        Outcome::Success,
+        Outcome::Rejected,
+        Outcome::Crashed,
+        Outcome::NonzeroExit,
+        Outcome::MalformedOutput,
+        Outcome::SpawnFailed,
+        Outcome::LimitExceeded,
+        Outcome::TimedOut,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Success => "success",
+            Outcome::Rejected => "rejected",
+            Outcome::Crashed => "crashed",
+            Outcome::NonzeroExit => "nonzero-exit",
+            Outcome::MalformedOutput => "malformed-output",
+            Outcome::SpawnFailed => "spawn-failed",
+            Outcome::LimitExceeded => "limit-exceeded",
+            Outcome::TimedOut => "timed-out",
+        }
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Number of executions with each outcome
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutcomeCounts([usize; Outcome::ALL.len()]);
+
+impl OutcomeCounts {
+    pub fn record(&mut self, outcome: Outcome) {
+        self.0[outcome as usize] += 1;
+    }
+
+    pub fn get(&self, outcome: Outcome) -> usize {
+        self.0[outcome as usize]
+    }
+}
+
+impl serde::Serialize for OutcomeCounts {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(Some(Outcome::ALL.len()))?;
+        for outcome in Outcome::ALL {
+            map.serialize_entry(outcome.as_str(), &self.get(outcome))?;
+        }
+        map.end()
+    }
 }
 
 impl<'a> ImplementationExecutor<'a, Initial> {
     pub fn new(implementation: &'a Implementation) -> Self {
         Self {
             implementation,
+            servers: Mutex::new(HashMap::new()),
+            cpu: None,
             _stage: std::marker::PhantomData,
         }
     }
@@ -40,6 +212,26 @@ impl<'a> ImplementationExecutor<'a, Initial> {
     }
 
     pub fn build(self) -> Result<ImplementationExecutor<'a, Built>> {
+        let config = &self.implementation.config;
+        if config.protocol() == Protocol::Server {
+            if let Some(task) = config
+                .tasks
+                .iter()
+                .find(|(_, task_config)| task_config.input == InputMode::File)
+                .map(|(task, _)| task)
+            {
+                anyhow::bail!(
+                    "The {} task reads its input from a file, which isn't supported in server mode",
+                    task
+                );
+            }
+
+            // Limits would apply to the server over its whole life rather than to each request
+            if config.limits() != ResourceLimits::default() {
+                anyhow::bail!("Resource limits aren't supported in server mode");
+            }
+        }
+
         // Get build command from execution config or derive from project type
         let build_command = self
             .implementation
@@ -61,9 +253,9 @@ impl<'a> ImplementationExecutor<'a, Initial> {
 
             let mut cmd = Command::new(&parts[0]);
             cmd.args(&parts[1..])
-                .current_dir(&self.implementation.path)
                 .stdout(Stdio::inherit())
                 .stderr(Stdio::inherit());
+            configure_environment(&mut cmd, self.implementation, None);
 
             let output = cmd.output()?;
 
@@ -82,6 +274,8 @@ impl<'a> ImplementationExecutor<'a, Initial> {
 
         Ok(ImplementationExecutor {
             implementation: self.implementation,
+            servers: self.servers,
+            cpu: self.cpu,
             _stage: std::marker::PhantomData,
         })
     }
@@ -93,12 +287,151 @@ impl ImplementationExecutor<'_, Built> {
         self.implementation
     }
 
+    /// Pin every run to the CPU, which must have been checked with
+    /// [`isolation::RunConditions::inspect`]
+    pub fn pinned_to(mut self, cpu: Option<usize>) -> Self {
+        self.cpu = cpu;
+        self
+    }
+
     pub fn execute(
         &self,
         content: &[u8],
         task: TaskType,
         games: &[Game],
+        encoding: Option<Encoding>,
+    ) -> Result<ExecutionResult> {
+        self.execute_with_path(content, None, task, games, encoding)
+    }
+
+    /// Execute the task on the content, where `path` optionally names a file
+    /// that already holds the content. Implementations that read their input
+    /// from a file are given the path rather than a temporary copy.
+    pub fn execute_with_path(
+        &self,
+        content: &[u8],
+        path: Option<&Path>,
+        task: TaskType,
+        games: &[Game],
+        encoding: Option<Encoding>,
     ) -> Result<ExecutionResult> {
+        let config = &self.implementation.config;
+        let mut cmd = match self.command(task) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                return Ok(ExecutionResult::SpawnFailed {
+                    error: e.to_string(),
+                })
+            }
+        };
+
+        match config.protocol() {
+            Protocol::Process => {
+                // Kept until the run completes
+                let temp_file;
+                let stdin = match config.input_for_task(task) {
+                    InputMode::Stdin => content,
+                    InputMode::File => {
+                        let path = match path {
+                            Some(path) => path,
+                            None => {
+                                temp_file = write_temp_file(content)?;
+                                temp_file.path()
+                            }
+                        };
+                        cmd.arg("--input").arg(path).stdin(Stdio::null());
+                        &[]
+                    }
+                };
+
+                execute_process(cmd, config, stdin, task, games, encoding)
+            }
+            Protocol::Server => {
+                let request = Request {
+                    task,
+                    games,
+                    encoding,
+                    payload: content,
+                };
+                self.execute_server(cmd, &request, config.timeout_for_task(task))
+            }
+        }
+    }
+
+    /// Send the input to an idle server for the task, starting one if they
+    /// are all busy. A server that crashes or times out is stopped rather
+    /// than returned, so a new one is started for a later request.
+    fn execute_server(
+        &self,
+        mut cmd: Command,
+        request: &Request,
+        timeout: Duration,
+    ) -> Result<ExecutionResult> {
+        let idle = self
+            .servers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(&request.task)
+            .and_then(Vec::pop);
+        let mut process = match idle {
+            Some(process) => process,
+            None => {
+                cmd.arg("--server");
+                match ServerProcess::spawn(&mut cmd, self.implementation.config.max_output_bytes())
+                {
+                    Ok(process) => process,
+                    Err(e) => {
+                        return Ok(ExecutionResult::SpawnFailed {
+                            error: format!("{:#}", e),
+                        })
+                    }
+                }
+            }
+        };
+
+        let start = Instant::now();
+        let reply = process.request(request, timeout);
+        if matches!(reply, Ok(Reply::Response(_))) && !process.is_stopped() {
+            self.servers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(request.task)
+                .or_default()
+                .push(process);
+        }
+
+        let result = match reply? {
+            Reply::Response(Response::Ok(body)) => parse_output(
+                self.implementation.config.protocol_version,
+                &body,
+                &[],
+                start.elapsed(),
+            ),
+            Reply::Response(Response::Error(body)) => ExecutionResult::NonzeroExit {
+                code: None,
+                error: String::from_utf8_lossy(&body).trim().to_string(),
+            },
+            Reply::TimedOut => ExecutionResult::Timeout { timeout },
+            Reply::Exited { status, stderr } if status.success() => {
+                ExecutionResult::MalformedOutput {
+                    error: format!("Server exited before responding: {}", stderr.trim()),
+                }
+            }
+            Reply::Exited { status, stderr } => exit_failure(
+                status,
+                &[],
+                stderr.as_bytes(),
+                self.implementation.config.limits(),
+                None,
+            ),
+            Reply::Malformed(error) => ExecutionResult::MalformedOutput { error },
+        };
+        Ok(result)
+    }
+
+    /// The implementation's run command for the task with piped stdio, before
+    /// any task arguments
+    fn command(&self, task: TaskType) -> Result<Command> {
         // Get run command from execution config or derive from project type
         let run_command = self
             .implementation
@@ -118,74 +451,381 @@ impl ImplementationExecutor<'_, Built> {
         }
 
         let mut cmd = Command::new(&parts[0]);
-        cmd.args(&parts[1..]).arg("--task").arg(task.as_str());
-
-        // Add each game as a separate --game argument
-        for game in games {
-            cmd.arg("--game").arg(game.as_str());
-        }
-
-        cmd.current_dir(&self.implementation.path)
+        cmd.args(&parts[1..])
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
+        configure_environment(&mut cmd, self.implementation, Some(task));
+        limits::apply(&mut cmd, self.implementation.config.limits());
+        if let Some(cpu) = self.cpu {
+            isolation::pin(&mut cmd, cpu);
+        }
 
-        let mut child = cmd.spawn()?;
-
-        // Write content to stdin
-        if let Some(stdin) = child.stdin.take() {
-            use std::io::Write;
-            let mut stdin = stdin;
-            stdin.write_all(content)?;
-            drop(stdin); // Close stdin to signal EOF
+        // Run in a new process group so a timeout also kills anything the
+        // implementation spawned (eg: the binary behind `cargo run`)
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
         }
 
-        let output = child.wait_with_output()?;
+        Ok(cmd)
+    }
+}
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let lines: Vec<&str> = stdout.lines().collect();
+/// Host variables kept in a cleared environment, as toolchains can't be found
+/// without them
+const PRESERVED_ENV_VARS: &[&str] = &["PATH", "HOME"];
+
+/// Apply the implementation's working directory and environment for the task,
+/// or for building when there is no task
+fn configure_environment(
+    cmd: &mut Command,
+    implementation: &Implementation,
+    task: Option<TaskType>,
+) {
+    let config = &implementation.config;
+    let working_dir = config.working_dir_for_task(task).unwrap_or(Path::new(""));
+    cmd.current_dir(implementation.path.join(working_dir));
 
-        // Parse the new two-line output format:
-        // Line 1: microseconds (as a number)
-        // Line 2: task output
-        if output.status.success() && lines.len() >= 2 {
-            if let Ok(microseconds) = lines[0].parse::<u64>() {
-                let elapsed = std::time::Duration::from_micros(microseconds);
-                return Ok(ExecutionResult::Success { elapsed });
+    if config.clear_env() {
+        cmd.env_clear();
+        for key in PRESERVED_ENV_VARS {
+            if let Some(value) = std::env::var_os(key) {
+                cmd.env(key, value);
             }
         }
+    }
 
-        // Handle failure case or unexpected format
-        let error_msg = if lines.len() >= 2 && lines[0].parse::<u128>().is_ok() {
-            // Format appears correct but process failed - use second line as error
-            lines[1].to_string()
-        } else {
-            // Combine stdout and stderr into a single error message
-            let mut combined_output = String::new();
+    cmd.envs(config.env_for_task(task));
+}
 
-            if !stdout.trim().is_empty() {
-                combined_output.push_str(stdout.trim());
-            }
+/// Run the implementation in a new process for the input
+fn execute_process(
+    mut cmd: Command,
+    config: &ImplementationConfig,
+    content: &[u8],
+    task: TaskType,
+    games: &[Game],
+    encoding: Option<Encoding>,
+) -> Result<ExecutionResult> {
+    let timeout = config.timeout_for_task(task);
+    cmd.arg("--task").arg(task.as_str());
 
-            if !stderr.trim().is_empty() {
-                if !combined_output.is_empty() {
-                    combined_output.push(' ');
-                }
-                combined_output.push_str(stderr.trim());
+    // Add each game as a separate --game argument
+    for game in games {
+        cmd.arg("--game").arg(game.as_str());
+    }
+
+    // Spare implementations from sniffing the encoding when it is known
+    if let Some(encoding) = encoding {
+        cmd.arg("--encoding").arg(encoding.as_str());
+    }
+
+    let start = Instant::now();
+    let child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            return Ok(ExecutionResult::SpawnFailed {
+                error: format!("{}: {}", cmd.get_program().to_string_lossy(), e),
+            })
+        }
+    };
+
+    let max_output = config.max_output_bytes();
+    let Some(run) = run_with_timeout(child, start, content, timeout, max_output)? else {
+        return Ok(ExecutionResult::Timeout { timeout });
+    };
+
+    let output = &run.output;
+    if !output.status.success() {
+        return Ok(exit_failure(
+            output.status,
+            &output.stdout,
+            &output.stderr,
+            config.limits(),
+            run.usage,
+        ));
+    }
+
+    // A truncated result can't be trusted
+    if run.stdout_truncated {
+        return Ok(ExecutionResult::MalformedOutput {
+            error: format!("Output exceeded the limit of {} bytes", max_output),
+        });
+    }
+
+    let mut result = parse_output(
+        config.protocol_version,
+        &output.stdout,
+        &output.stderr,
+        run.wall_time,
+    );
+    if let ExecutionResult::Success { usage, stderr, .. } = &mut result {
+        *usage = run.usage;
+        *stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    }
+    Ok(result)
+}
+
+/// Write the content to a temporary file for implementations that read their
+/// input from a file
+fn write_temp_file(content: &[u8]) -> Result<tempfile::NamedTempFile> {
+    use anyhow::Context;
+
+    let mut file =
+        tempfile::NamedTempFile::new().context("Failed to create a temporary input file")?;
+    file.write_all(content)
+        .and_then(|()| file.flush())
+        .context("Failed to write the temporary input file")?;
+    Ok(file)
+}
+
+/// Interpret the output of a run that completed and took the given wall time
+fn parse_output(
+    version: ProtocolVersion,
+    stdout: &[u8],
+    stderr: &[u8],
+    wall_time: Duration,
+) -> ExecutionResult {
+    match version {
+        ProtocolVersion::V1 => parse_text_output(stdout, stderr, wall_time),
+        ProtocolVersion::V2 => parse_json_output(stdout, wall_time),
+    }
+}
+
+/// Interpret version 1 output: the elapsed microseconds on the first line
+/// followed by the result, or by `rejected` and an optional reason when the
+/// input is invalid
+fn parse_text_output(stdout: &[u8], stderr: &[u8], wall_time: Duration) -> ExecutionResult {
+    let text = String::from_utf8_lossy(stdout);
+    let lines: Vec<&str> = text.lines().collect();
+
+    let Some(elapsed) = lines.first().and_then(|x| x.parse::<u64>().ok()) else {
+        return ExecutionResult::MalformedOutput {
+            error: format!(
+                "Expected elapsed microseconds on the first line: {}",
+                combined_output(stdout, stderr)
+            ),
+        };
+    };
+
+    match lines.get(1) {
+        None => ExecutionResult::MalformedOutput {
+            error: String::from("Missing result after the elapsed time"),
+        },
+        Some(&"rejected") => ExecutionResult::Rejected {
+            reason: lines[2..].join("\n").trim().to_string(),
+        },
+        Some(_) => ExecutionResult::Success {
+            elapsed: Duration::from_micros(elapsed),
+            wall_time,
+            output: lines[1..].join("\n"),
+            stderr: String::new(),
+            usage: None,
+            warnings: Vec::new(),
+            phases: Vec::new(),
+        },
+    }
+}
+
+/// Interpret version 2 output: a JSON object with a result or an error
+fn parse_json_output(stdout: &[u8], wall_time: Duration) -> ExecutionResult {
+    match output::parse_v2(stdout) {
+        Ok(ParsedOutput::Success {
+            elapsed,
+            output,
+            warnings,
+            phases,
+        }) => ExecutionResult::Success {
+            elapsed,
+            wall_time,
+            output,
+            stderr: String::new(),
+            usage: None,
+            warnings,
+            phases,
+        },
+        Ok(ParsedOutput::Error {
+            kind: OutputErrorKind::Rejected,
+            message,
+        }) => ExecutionResult::Rejected { reason: message },
+        Ok(ParsedOutput::Error {
+            kind: OutputErrorKind::Failed,
+            message,
+        }) => ExecutionResult::NonzeroExit {
+            code: None,
+            error: message,
+        },
+        Err(error) => ExecutionResult::MalformedOutput { error },
+    }
+}
+
+/// Classify a run that exited unsuccessfully
+fn exit_failure(
+    status: ExitStatus,
+    stdout: &[u8],
+    stderr: &[u8],
+    limits: ResourceLimits,
+    usage: Option<ResourceUsage>,
+) -> ExecutionResult {
+    let text = String::from_utf8_lossy(stdout);
+    let lines: Vec<&str> = text.lines().collect();
+
+    let error = if lines.len() >= 2 && lines[0].parse::<u64>().is_ok() {
+        // Format appears correct but process failed - use second line as error
+        lines[1].to_string()
+    } else {
+        combined_output(stdout, stderr)
+    };
+
+    if let Some(limit) = limits::exceeded(limits, status, stderr, usage) {
+        return ExecutionResult::LimitExceeded { limit, error };
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return ExecutionResult::Crashed {
+                signal: signal_name(signal),
+                error,
+            };
+        }
+    }
+
+    ExecutionResult::NonzeroExit {
+        code: status.code(),
+        error,
+    }
+}
+
+/// Stdout and stderr combined into a single message
+fn combined_output(stdout: &[u8], stderr: &[u8]) -> String {
+    let stdout = String::from_utf8_lossy(stdout);
+    let stderr = String::from_utf8_lossy(stderr);
+    let parts: Vec<&str> = [stdout.trim(), stderr.trim()]
+        .into_iter()
+        .filter(|x| !x.is_empty())
+        .collect();
+
+    if parts.is_empty() {
+        String::from("no output")
+    } else {
+        parts.join(" ")
+    }
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGILL => "SIGILL",
+        libc::SIGINT => "SIGINT",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGSYS => "SIGSYS",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGXCPU => "SIGXCPU",
+        libc::SIGXFSZ => "SIGXFSZ",
+        _ => return format!("signal {}", signal),
+    };
+    String::from(name)
+}
+
+/// A process that exited within its timeout
+struct CompletedRun {
+    /// Output with up to the maximum captured from each stream, followed by a
+    /// marker if the rest was left out
+    output: Output,
+    stdout_truncated: bool,
+    wall_time: Duration,
+    usage: Option<ResourceUsage>,
+}
+
+/// Run a child started at the given instant to completion with the input
+/// written to its stdin, capturing up to `max_output` bytes of stdout and
+/// stderr each. Returns `None` if the child didn't finish within the timeout,
+/// in which case its process group was killed.
+fn run_with_timeout(
+    mut child: Child,
+    start: Instant,
+    input: &[u8],
+    timeout: Duration,
+    max_output: usize,
+) -> Result<Option<CompletedRun>> {
+    let pid = child.id();
+    let stdin = child.stdin.take();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    std::thread::scope(|scope| {
+        // Pump the pipes on their own threads so a child that stops reading
+        // its input or never exits can't block us past the timeout
+        scope.spawn(move || {
+            if let Some(mut stdin) = stdin {
+                // The child may exit without reading all of its input
+                let _ = stdin.write_all(input);
             }
+        });
+        let stdout = scope.spawn(move || capture::read_capped(stdout, max_output));
+        let stderr = scope.spawn(move || capture::read_capped(stderr, max_output));
 
-            if combined_output.is_empty() {
-                format!("Process exited with code: {:?}", output.status.code())
-            } else {
-                combined_output
+        let (sender, receiver) = mpsc::channel();
+        scope.spawn(move || {
+            let status = rusage::wait(child);
+            sender.send(status.map(|(status, usage)| (status, start.elapsed(), usage)))
+        });
+
+        let status = match receiver.recv_timeout(timeout) {
+            Ok(status) => Some(status?),
+            Err(_) => {
+                kill_process_group(pid);
+
+                // Reap the killed child
+                receiver.recv()??;
+                None
             }
         };
 
-        Ok(ExecutionResult::Error { error: error_msg })
+        let stdout: CappedBuffer = stdout.join().expect("stdout reader panicked")?;
+        let stderr: CappedBuffer = stderr.join().expect("stderr reader panicked")?;
+        Ok(status.map(|(status, wall_time, usage)| CompletedRun {
+            stdout_truncated: stdout.is_truncated(),
+            output: Output {
+                status,
+                stdout: stdout.into_bytes(),
+                stderr: stderr.into_bytes(),
+            },
+            wall_time,
+            usage,
+        }))
+    })
+}
+
+#[cfg(unix)]
+pub(crate) fn kill_process_group(pid: u32) {
+    // SAFETY: kill has no memory safety requirements. The process group was
+    // created for the child, so only it and its descendants are signalled.
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
     }
 }
 
+#[cfg(not(unix))]
+pub(crate) fn kill_process_group(pid: u32) {
+    // Terminate the process tree
+    let _ = Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &pid.to_string()])
+        .output();
+}
+
 pub(crate) struct ProjectTypeConfig {
     pub build_command: Option<&'static str>,
     pub run_command: &'static str,
@@ -366,4 +1006,628 @@ mod tests {
         assert_eq!(nodejs_config.run_command, "node main.js");
         assert_eq!(nodejs_config.build_command, Some("npm install"));
     }
+
+    #[test]
+    fn test_parse_output() {
+        use super::*;
+
+        let wall = Duration::from_millis(1);
+        assert!(matches!(
+            parse_text_output(b"15\n3\n", b"", wall),
+            ExecutionResult::Success { ref output, .. } if output == "3"
+        ));
+        assert!(matches!(
+            parse_text_output(b"15\nrejected\nunexpected }\n", b"", wall),
+            ExecutionResult::Rejected { ref reason } if reason == "unexpected }"
+        ));
+        assert!(matches!(
+            parse_text_output(b"15\nrejected\n", b"", wall),
+            ExecutionResult::Rejected { ref reason } if reason.is_empty()
+        ));
+        assert!(matches!(
+            parse_text_output(b"15\n", b"", wall),
+            ExecutionResult::MalformedOutput { .. }
+        ));
+        assert!(matches!(
+            parse_text_output(b"abc\n", b"oops", wall),
+            ExecutionResult::MalformedOutput { ref error } if error.ends_with("abc oops")
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_failures() {
+        use super::*;
+
+        let cases = [
+            (
+                "kill -SEGV $$",
+                Outcome::Crashed,
+                "Crashed (SIGSEGV): no output",
+            ),
+            (
+                "echo oops >&2; exit 2",
+                Outcome::NonzeroExit,
+                "Exited with code 2: oops",
+            ),
+            (
+                "echo not a number",
+                Outcome::MalformedOutput,
+                "Malformed output: Expected elapsed microseconds on the first line: not a number",
+            ),
+        ];
+
+        for (script, outcome, message) in cases {
+            let temp_dir = tempfile::TempDir::new().unwrap();
+            let implementation = shell_implementation(temp_dir.path(), script, "process", 10.0);
+            let executor = ImplementationExecutor::new(&implementation)
+                .build()
+                .unwrap();
+            let result = executor
+                .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+                .unwrap();
+            assert_eq!(result.outcome(), outcome);
+            assert_eq!(result.error_message().unwrap(), message);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_spawn_failure() {
+        use super::*;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut implementation = shell_implementation(temp_dir.path(), "true", "process", 10.0);
+        implementation
+            .config
+            .execution
+            .as_mut()
+            .unwrap()
+            .run_command = Some(String::from("./does-not-exist"));
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+
+        let result = executor
+            .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        assert_eq!(result.outcome(), Outcome::SpawnFailed);
+    }
+
+    /// An implementation that runs a shell script with the given protocol
+    #[cfg(unix)]
+    fn shell_implementation(
+        dir: &std::path::Path,
+        script: &str,
+        protocol: &str,
+        timeout: f64,
+    ) -> super::Implementation {
+        std::fs::write(dir.join("run.sh"), script).unwrap();
+        let config = format!(
+            r#"
+            name = "shell"
+            project-type = "make"
+
+            [execution]
+            build-command = "true"
+            run-command = "sh run.sh"
+            protocol = "{}"
+
+            [tasks.can-parse]
+            games = ["eu4"]
+            timeout = {}
+            "#,
+            protocol, timeout
+        );
+
+        super::Implementation {
+            name: String::from("shell"),
+            path: dir.to_path_buf(),
+            config: toml::from_str(&config).unwrap(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_success() {
+        use super::*;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let implementation = shell_implementation(
+            temp_dir.path(),
+            "cat >/dev/null; echo 15; echo 3",
+            "process",
+            10.0,
+        );
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+
+        let result = executor
+            .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        match result {
+            ExecutionResult::Success {
+                elapsed,
+                wall_time,
+                output,
+                usage,
+                ..
+            } => {
+                assert_eq!(output, "3");
+                assert_eq!(elapsed, Duration::from_micros(15));
+                assert!(wall_time > elapsed);
+                assert_eq!(usage.is_some(), cfg!(target_os = "linux"));
+            }
+            _ => panic!("Expected Success variant, got {:?}", result),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_protocol_v2() {
+        use super::*;
+        use crate::core::config::ProtocolVersion;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let script = r#"cat >/dev/null; echo '{"elapsed_us": 15, "result": "3", "warnings": ["w"], "phases": [{"name": "parse", "elapsed_us": 10}]}'"#;
+        let mut implementation = shell_implementation(temp_dir.path(), script, "process", 10.0);
+        implementation.config.protocol_version = ProtocolVersion::V2;
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+
+        let result = executor
+            .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        match result {
+            ExecutionResult::Success {
+                elapsed,
+                output,
+                warnings,
+                phases,
+                ..
+            } => {
+                assert_eq!(output, "3");
+                assert_eq!(elapsed, Duration::from_micros(15));
+                assert_eq!(warnings, vec![String::from("w")]);
+                assert_eq!(
+                    phases,
+                    vec![(String::from("parse"), Duration::from_micros(10))]
+                );
+            }
+            _ => panic!("Expected Success variant, got {:?}", result),
+        }
+
+        // Version 1 output isn't accepted once an implementation opts into v2
+        std::fs::write(
+            temp_dir.path().join("run.sh"),
+            "cat >/dev/null; echo 15; echo 3",
+        )
+        .unwrap();
+        let result = executor
+            .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        assert_eq!(result.outcome(), Outcome::MalformedOutput);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_file_input() {
+        use super::*;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let script = r#"
+            while [ $# -gt 0 ]; do
+                if [ "$1" = --input ]; then input="$2"; fi
+                shift
+            done
+            echo 15
+            cat "$input"
+        "#;
+        let mut implementation = shell_implementation(temp_dir.path(), script, "process", 10.0);
+        let task_config = implementation.config.tasks.get_mut(&TaskType::CanParse);
+        task_config.unwrap().input = InputMode::File;
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+
+        // The content is written to a temporary file
+        let result = executor
+            .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        assert!(matches!(result, ExecutionResult::Success { output, .. } if output == "a=b"));
+
+        // Or the file already holding it is passed along
+        let path = temp_dir.path().join("input.txt");
+        std::fs::write(&path, "c=d").unwrap();
+        let result = executor
+            .execute_with_path(b"c=d", Some(&path), TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        assert!(matches!(result, ExecutionResult::Success { output, .. } if output == "c=d"));
+
+        // Servers handle many inputs over stdin
+        let mut implementation = shell_implementation(temp_dir.path(), script, "server", 10.0);
+        let task_config = implementation.config.tasks.get_mut(&TaskType::CanParse);
+        task_config.unwrap().input = InputMode::File;
+        assert!(ImplementationExecutor::new(&implementation)
+            .build()
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_environment() {
+        use super::*;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let script = r#"cat >/dev/null; echo 15; basename "$PWD"; env"#;
+        let mut implementation = shell_implementation(temp_dir.path(), script, "process", 10.0);
+
+        // The task runs from a subdirectory holding its own script
+        std::fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        std::fs::write(temp_dir.path().join("sub").join("run.sh"), script).unwrap();
+        let execution = implementation.config.execution.as_mut().unwrap();
+        execution.clear_env = true;
+        execution.env.insert(String::from("A"), String::from("1"));
+        execution.env.insert(String::from("B"), String::from("2"));
+        let task_config = implementation
+            .config
+            .tasks
+            .get_mut(&TaskType::CanParse)
+            .unwrap();
+        task_config.env.insert(String::from("B"), String::from("3"));
+        task_config.working_dir = Some(std::path::PathBuf::from("sub"));
+
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+        let result = executor
+            .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        let ExecutionResult::Success { output, .. } = result else {
+            panic!("Expected Success variant, got {:?}", result);
+        };
+
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("sub"));
+        let env: Vec<&str> = lines.collect();
+        assert!(env.contains(&"A=1"));
+        assert!(env.contains(&"B=3"));
+
+        // Host variables don't leak into the cleared environment
+        let host_var = std::env::vars()
+            .map(|(key, _)| key)
+            .find(|key| key.starts_with("CARGO"));
+        if let Some(host_var) = host_var {
+            let prefix = format!("{}=", host_var);
+            assert!(!env.iter().any(|line| line.starts_with(&prefix)));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_execute_limit_exceeded() {
+        use super::*;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cases = [
+            (
+                r#"cat >/dev/null; echo "memory allocation of 64 bytes failed" >&2; kill -ABRT $$"#,
+                "Memory limit exceeded: memory allocation of 64 bytes failed",
+            ),
+            (
+                "cat >/dev/null; while :; do :; done",
+                "CPU time limit exceeded: no output",
+            ),
+        ];
+
+        for (script, message) in cases {
+            let mut implementation = shell_implementation(temp_dir.path(), script, "process", 10.0);
+            let execution = implementation.config.execution.as_mut().unwrap();
+            execution.limits.memory_mb = Some(1024);
+            execution.limits.cpu_seconds = Some(1);
+            let executor = ImplementationExecutor::new(&implementation)
+                .build()
+                .unwrap();
+
+            let result = executor
+                .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+                .unwrap();
+            assert_eq!(result.outcome(), Outcome::LimitExceeded, "{:?}", result);
+            assert_eq!(result.error_message().unwrap(), message);
+        }
+
+        // A server's limits would accumulate over every request it serves
+        let mut implementation = shell_implementation(temp_dir.path(), "", "server", 10.0);
+        let execution = implementation.config.execution.as_mut().unwrap();
+        execution.limits.cpu_seconds = Some(1);
+        assert!(ImplementationExecutor::new(&implementation)
+            .build()
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_output_capture() {
+        use super::*;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let implementation_for = |script: &str| {
+            let mut implementation = shell_implementation(temp_dir.path(), script, "process", 10.0);
+            let execution = implementation.config.execution.as_mut().unwrap();
+            execution.max_output_kb = Some(1);
+            implementation
+        };
+
+        // Diagnostics written before the input is read don't deadlock on a
+        // full pipe, and are kept apart from the result
+        let implementation = implementation_for(
+            "head -c 200000 /dev/zero | tr '\\0' x >&2; cat >/dev/null; echo 15; echo 3",
+        );
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+        let input = vec![b'a'; 1024 * 1024];
+        let result = executor
+            .execute(&input, TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        let ExecutionResult::Success { output, stderr, .. } = result else {
+            panic!("Expected Success variant, got {:?}", result);
+        };
+        assert_eq!(output, "3");
+        assert_eq!(
+            stderr,
+            format!("{}\n[truncated 198976 bytes]", "x".repeat(1024))
+        );
+
+        // A result that doesn't fit can't be trusted
+        let implementation =
+            implementation_for("cat >/dev/null; echo 15; head -c 5000 /dev/zero | tr '\\0' y");
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+        let result = executor
+            .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        assert_eq!(
+            result.error_message().unwrap(),
+            "Malformed output: Output exceeded the limit of 1024 bytes"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_timeout_kills_process_group() {
+        use super::*;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let implementation =
+            shell_implementation(temp_dir.path(), "sleep 30 & wait", "process", 0.2);
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        let result = executor
+            .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        assert!(matches!(
+            result,
+            ExecutionResult::Timeout { timeout } if timeout == Duration::from_millis(200)
+        ));
+
+        // The backgrounded sleep holds the output pipes open, so returning
+        // promptly means it was killed along with the shell
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    /// Server script that answers every request with a count of 3 after
+    /// recording that it started
+    #[cfg(unix)]
+    const SERVER_SCRIPT: &str = r#"
+[ "$1" = "--server" ] || exit 9
+echo started >> starts
+if [ "$(wc -l < starts)" -eq 1 ] && [ -n "$FAIL_FIRST" ]; then
+    read -r header
+    [ "$FAIL_FIRST" = "crash" ] && { echo boom >&2; exit 3; }
+    sleep 30
+fi
+while read -r header; do
+    head -c "${header##*length=}" > /dev/null
+    sleep "${DELAY:-0}"
+    printf 'ok 4\n15\n3'
+done
+"#;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_server() {
+        use super::*;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let implementation = shell_implementation(temp_dir.path(), SERVER_SCRIPT, "server", 10.0);
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+
+        for content in [&b"a=b"[..], b"", b"c={ d=e }\n"] {
+            let result = executor
+                .execute(content, TaskType::CanParse, &[Game::Eu4], None)
+                .unwrap();
+            assert!(matches!(
+                result,
+                ExecutionResult::Success { ref output, .. } if output == "3"
+            ));
+        }
+
+        // Every request was served by the same process
+        let starts = std::fs::read_to_string(temp_dir.path().join("starts")).unwrap();
+        assert_eq!(starts.lines().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_server_concurrent() {
+        use super::*;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let script = format!("DELAY=0.5\n{}", SERVER_SCRIPT);
+        let implementation = shell_implementation(temp_dir.path(), &script, "server", 10.0);
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+
+        // Requests made at the same time are served by a server each
+        std::thread::scope(|scope| {
+            let runs: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        executor
+                            .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+                            .unwrap()
+                    })
+                })
+                .collect();
+            for run in runs {
+                assert!(matches!(
+                    run.join().unwrap(),
+                    ExecutionResult::Success { .. }
+                ));
+            }
+        });
+
+        // Which are then reused
+        for _ in 0..2 {
+            let result = executor
+                .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+                .unwrap();
+            assert!(matches!(result, ExecutionResult::Success { .. }));
+        }
+        let starts = std::fs::read_to_string(temp_dir.path().join("starts")).unwrap();
+        assert_eq!(starts.lines().count(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_server_task_environment() {
+        use super::*;
+
+        let script = r#"
+[ "$1" = "--server" ] || exit 9
+while read -r header; do
+    head -c "${header##*length=}" > /dev/null
+    printf 'ok %d\n15\n%s' $((3 + ${#MODE})) "$MODE"
+done
+"#;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut implementation = shell_implementation(temp_dir.path(), script, "server", 10.0);
+        let mut localisation = implementation.config.tasks[&TaskType::CanParse].clone();
+        localisation
+            .env
+            .insert(String::from("MODE"), String::from("loc"));
+        implementation
+            .config
+            .tasks
+            .insert(TaskType::Localisation, localisation);
+        let execution = implementation.config.execution.as_mut().unwrap();
+        execution
+            .env
+            .insert(String::from("MODE"), String::from("all"));
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+
+        // Each task is served by a server started with its own variables
+        for (task, expected) in [
+            (TaskType::CanParse, "all"),
+            (TaskType::Localisation, "loc"),
+            (TaskType::CanParse, "all"),
+        ] {
+            let result = executor.execute(b"a=b", task, &[Game::Eu4], None).unwrap();
+            assert!(matches!(
+                result,
+                ExecutionResult::Success { ref output, .. } if output == expected
+            ));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_server_responds_without_reading() {
+        use super::*;
+
+        // Answers the first request without reading its payload
+        let script = r#"
+[ "$1" = "--server" ] || exit 9
+echo started >> starts
+read -r header
+if [ "$(wc -l < starts)" -eq 1 ]; then
+    printf 'error 4\nnope'
+    sleep 30
+fi
+head -c "${header##*length=}" > /dev/null
+printf 'ok 4\n15\n3'
+sleep 30
+"#;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let implementation = shell_implementation(temp_dir.path(), script, "server", 1.0);
+        let executor = ImplementationExecutor::new(&implementation)
+            .build()
+            .unwrap();
+
+        // Far larger than a pipe buffer, so writing it blocks
+        let payload = vec![b'a'; 8 * 1024 * 1024];
+        let start = Instant::now();
+        let result = executor
+            .execute(&payload, TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        assert!(matches!(
+            result,
+            ExecutionResult::NonzeroExit { code: None, ref error } if error == "nope"
+        ));
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        // The server was stopped rather than left mid request
+        let result = executor
+            .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+            .unwrap();
+        assert!(matches!(result, ExecutionResult::Success { .. }));
+        let starts = std::fs::read_to_string(temp_dir.path().join("starts")).unwrap();
+        assert_eq!(starts.lines().count(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_server_restarts() {
+        use super::*;
+
+        for (failure, timeout) in [("crash", 10.0), ("hang", 0.2)] {
+            let temp_dir = tempfile::TempDir::new().unwrap();
+            let script = format!("FAIL_FIRST={}\n{}", failure, SERVER_SCRIPT);
+            let implementation = shell_implementation(temp_dir.path(), &script, "server", timeout);
+            let executor = ImplementationExecutor::new(&implementation)
+                .build()
+                .unwrap();
+
+            let result = executor
+                .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+                .unwrap();
+            match failure {
+                "crash" => assert!(matches!(
+                    result,
+                    ExecutionResult::NonzeroExit { code: Some(3), ref error } if error.contains("boom")
+                )),
+                _ => assert!(matches!(result, ExecutionResult::Timeout { .. })),
+            }
+
+            let result = executor
+                .execute(b"a=b", TaskType::CanParse, &[Game::Eu4], None)
+                .unwrap();
+            assert!(matches!(result, ExecutionResult::Success { .. }));
+
+            let starts = std::fs::read_to_string(temp_dir.path().join("starts")).unwrap();
+            assert_eq!(starts.lines().count(), 2);
+        }
+    }
 }
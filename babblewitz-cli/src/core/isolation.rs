@@ -0,0 +1,180 @@
+use anyhow::Result;
+use std::process::Command;
+
+/// One-minute load average above which other processes are likely competing
+/// with benchmarks for the CPU
+const HIGH_LOAD_AVERAGE: f64 = 1.0;
+
+/// The frequency governor that keeps the CPU at a steady, maximum frequency
+const STABLE_GOVERNOR: &str = "performance";
+
+/// Conditions that benchmarks ran under in isolation mode
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RunConditions {
+    /// CPU that implementations were pinned to
+    pub cpu: usize,
+    /// Load averages over the last 1, 5, and 15 minutes
+    pub load_average: Option<[f64; 3]>,
+    /// Frequency governor of the pinned CPU
+    pub governor: Option<String>,
+    /// Conditions that could disturb the timings
+    pub warnings: Vec<String>,
+}
+
+impl RunConditions {
+    /// Check that implementations can be pinned to the CPU, and inspect the
+    /// system for anything that could disturb benchmarks
+    pub fn inspect(cpu: usize) -> Result<RunConditions> {
+        check_cpu(cpu)?;
+
+        let load_average = std::fs::read_to_string("/proc/loadavg")
+            .ok()
+            .and_then(|x| parse_load_average(&x));
+        let governor_path = format!(
+            "/sys/devices/system/cpu/cpu{}/cpufreq/scaling_governor",
+            cpu
+        );
+        let governor = std::fs::read_to_string(governor_path)
+            .ok()
+            .map(|x| x.trim().to_string());
+
+        let mut warnings = Vec::new();
+        if let Some([load, ..]) = load_average {
+            if load > HIGH_LOAD_AVERAGE {
+                warnings.push(format!(
+                    "Load average over the last minute is {:.2}, so other processes may disturb the timings",
+                    load
+                ));
+            }
+        }
+        if let Some(governor) = governor.as_deref().filter(|x| *x != STABLE_GOVERNOR) {
+            warnings.push(format!(
+                "CPU {} uses the '{}' frequency governor, so frequency changes may disturb the timings (the '{}' governor is steadier)",
+                cpu, governor, STABLE_GOVERNOR
+            ));
+        }
+
+        Ok(RunConditions {
+            cpu,
+            load_average,
+            governor,
+            warnings,
+        })
+    }
+
+    /// Describe the conditions on one line
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("pinned to CPU {}", self.cpu)];
+        if let Some([one, five, fifteen]) = self.load_average {
+            parts.push(format!(
+                "load average {:.2} {:.2} {:.2}",
+                one, five, fifteen
+            ));
+        }
+        if let Some(governor) = &self.governor {
+            parts.push(format!("'{}' governor", governor));
+        }
+        parts.join(", ")
+    }
+}
+
+fn parse_load_average(loadavg: &str) -> Option<[f64; 3]> {
+    let mut fields = loadavg.split_whitespace().map(|x| x.parse::<f64>().ok());
+    Some([fields.next()??, fields.next()??, fields.next()??])
+}
+
+/// Fail unless this process may run on the CPU, as its children otherwise
+/// can't be pinned to it
+#[cfg(target_os = "linux")]
+fn check_cpu(cpu: usize) -> Result<()> {
+    if cpu >= libc::CPU_SETSIZE as usize {
+        anyhow::bail!("CPU {} is out of range", cpu);
+    }
+
+    // SAFETY: the set is zero initialized and sized for sched_getaffinity
+    let allowed = unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        libc::CPU_ISSET(cpu, &set)
+    };
+
+    if !allowed {
+        anyhow::bail!("CPU {} isn't available to run benchmarks on", cpu);
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn check_cpu(_cpu: usize) -> Result<()> {
+    anyhow::bail!("Pinning benchmarks to a CPU is only supported on Linux")
+}
+
+/// Restrict the command's process to the CPU before it executes
+#[cfg(target_os = "linux")]
+pub fn pin(cmd: &mut Command, cpu: usize) {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: the set is zero initialized and the CPU was checked to be in
+    // range by `RunConditions::inspect`
+    let set = unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(cpu, &mut set);
+        set
+    };
+
+    // SAFETY: the closure only calls sched_setaffinity, which is a plain
+    // system call, and doesn't allocate
+    unsafe {
+        cmd.pre_exec(move || {
+            if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Restrict the command's process to the CPU before it executes
+#[cfg(not(target_os = "linux"))]
+pub fn pin(_cmd: &mut Command, _cpu: usize) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_load_average() {
+        assert_eq!(
+            parse_load_average("0.52 1.05 2.00 1/234 5678\n"),
+            Some([0.52, 1.05, 2.0])
+        );
+        assert_eq!(parse_load_average("0.52"), None);
+        assert_eq!(parse_load_average("a b c"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_pin() {
+        let cpu = (0..libc::CPU_SETSIZE as usize)
+            .find(|x| check_cpu(*x).is_ok())
+            .unwrap();
+        let conditions = RunConditions::inspect(cpu).unwrap();
+        assert_eq!(conditions.cpu, cpu);
+        assert!(conditions
+            .summary()
+            .starts_with(&format!("pinned to CPU {}", cpu)));
+        assert!(RunConditions::inspect(libc::CPU_SETSIZE as usize).is_err());
+
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "grep Cpus_allowed_list /proc/self/status"]);
+        pin(&mut cmd, cpu);
+
+        let output = cmd.output().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            format!("Cpus_allowed_list:\t{}", cpu)
+        );
+    }
+}
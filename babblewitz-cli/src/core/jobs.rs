@@ -0,0 +1,77 @@
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of jobs to run at once when not specified: one per available CPU
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Apply a function to each item on a pool of worker threads, returning the
+/// results in the same order as the items. With a single job, everything runs
+/// on the calling thread.
+pub fn parallel_map<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if jobs <= 1 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let (f, next) = (&f, &next);
+    let mut results: Vec<Option<R>> = std::iter::repeat_with(|| None).take(items.len()).collect();
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.min(items.len()))
+            .map(|_| {
+                scope.spawn(move || {
+                    let mut completed = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break;
+                        };
+                        completed.push((index, f(item)));
+                    }
+                    completed
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            let completed = worker
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e));
+            for (index, result) in completed {
+                results[index] = Some(result);
+            }
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every item is processed"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_map_preserves_order() {
+        let items: Vec<u64> = (0..100).collect();
+        for jobs in [1, 4, 200] {
+            let results = parallel_map(&items, jobs, |x| {
+                // Stagger completion so workers finish out of order
+                std::thread::sleep(std::time::Duration::from_micros((100 - x) * 10));
+                x * 2
+            });
+            assert_eq!(results, items.iter().map(|x| x * 2).collect::<Vec<_>>());
+        }
+
+        assert_eq!(parallel_map(&[] as &[u64], 4, |x| *x), Vec::<u64>::new());
+    }
+}
@@ -0,0 +1,270 @@
+use crate::core::config::ResourceLimits;
+use crate::core::rusage::ResourceUsage;
+use std::process::{Command, ExitStatus};
+use std::time::Duration;
+
+/// Messages that runtimes write when an allocation fails (`ENOMEM`)
+const OUT_OF_MEMORY_MESSAGES: &[&str] = &[
+    "memory allocation of",
+    "out of memory",
+    "Out of memory",
+    "OutOfMemoryError",
+    "Cannot allocate memory",
+    "ENOMEM",
+    "bad_alloc",
+];
+
+/// Messages for `EMFILE`, from opening more files than allowed
+const TOO_MANY_FILES_MESSAGES: &[&str] = &["Too many open files", "EMFILE"];
+
+/// Messages for `EAGAIN`, from starting more processes or threads than allowed
+const TOO_MANY_PROCESSES_MESSAGES: &[&str] = &["Resource temporarily unavailable", "EAGAIN"];
+
+/// Number of lines at the end of stderr that are searched for the error a
+/// run ended with, skipping indented lines like stack frames
+const FINAL_ERROR_LINES: usize = 5;
+
+/// A resource limit that a run can exceed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Memory,
+    Cpu,
+    OpenFiles,
+    Processes,
+}
+
+impl Limit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Limit::Memory => "memory",
+            Limit::Cpu => "cpu",
+            Limit::OpenFiles => "open-files",
+            Limit::Processes => "processes",
+        }
+    }
+
+    /// Name of the limit for messages
+    pub fn description(&self) -> &'static str {
+        match self {
+            Limit::Memory => "Memory",
+            Limit::Cpu => "CPU time",
+            Limit::OpenFiles => "Open file",
+            Limit::Processes => "Process",
+        }
+    }
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Apply the limits to the command's process before it executes
+#[cfg(target_os = "linux")]
+pub fn apply(cmd: &mut Command, limits: ResourceLimits) {
+    use std::os::unix::process::CommandExt;
+
+    if limits == ResourceLimits::default() {
+        return;
+    }
+
+    let memory = limits.memory_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+    let rlimits = [
+        (libc::RLIMIT_AS, memory.map(|bytes| (bytes, bytes))),
+        // The hard limit follows a second later so the process is first sent
+        // SIGXCPU, which identifies why it was stopped
+        (
+            libc::RLIMIT_CPU,
+            limits
+                .cpu_seconds
+                .map(|secs| (secs, secs.saturating_add(1))),
+        ),
+        (libc::RLIMIT_NOFILE, limits.open_files.map(|n| (n, n))),
+        (libc::RLIMIT_NPROC, limits.processes.map(|n| (n, n))),
+    ];
+
+    // SAFETY: the closure only calls setrlimit, which is async-signal-safe,
+    // and doesn't allocate
+    unsafe {
+        cmd.pre_exec(move || {
+            for (resource, limit) in rlimits {
+                let Some((soft, hard)) = limit else {
+                    continue;
+                };
+
+                let rlimit = libc::rlimit {
+                    rlim_cur: soft,
+                    rlim_max: hard,
+                };
+                if libc::setrlimit(resource, &rlimit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Apply the limits to the command's process before it executes
+#[cfg(not(target_os = "linux"))]
+pub fn apply(_cmd: &mut Command, _limits: ResourceLimits) {}
+
+/// The limit a failed run most likely exceeded. The CPU limit is recognized
+/// by the signal the kernel sends on reaching it: `SIGXCPU` at the soft limit,
+/// or `SIGKILL` at the hard limit a second later. Otherwise the error the run
+/// ended with is matched against the messages runtimes print when an
+/// allocation, file, or thread is refused, as a `SIGKILL` alone could have
+/// come from anywhere.
+pub fn exceeded(
+    limits: ResourceLimits,
+    status: ExitStatus,
+    stderr: &[u8],
+    usage: Option<ResourceUsage>,
+) -> Option<Limit> {
+    if status.success() {
+        return None;
+    }
+
+    let signal = exit_signal(status);
+    if let Some(secs) = limits.cpu_seconds {
+        let cpu_time = usage.map(|x| x.cpu_time()).unwrap_or_default();
+        let hard_limit = Duration::from_secs(secs.saturating_add(1));
+        match signal {
+            Some(Signal::CpuLimit) => return Some(Limit::Cpu),
+            Some(Signal::Kill) if cpu_time >= hard_limit => return Some(Limit::Cpu),
+            _ => {}
+        }
+    }
+
+    let stderr = String::from_utf8_lossy(stderr);
+    let final_error: Vec<&str> = stderr
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with(char::is_whitespace))
+        .rev()
+        .take(FINAL_ERROR_LINES)
+        .collect();
+    let reports = |messages: &[&str]| {
+        final_error
+            .iter()
+            .any(|line| messages.iter().any(|message| line.contains(message)))
+    };
+
+    if limits.memory_mb.is_some() && reports(OUT_OF_MEMORY_MESSAGES) {
+        Some(Limit::Memory)
+    } else if limits.open_files.is_some() && reports(TOO_MANY_FILES_MESSAGES) {
+        Some(Limit::OpenFiles)
+    } else if limits.processes.is_some() && reports(TOO_MANY_PROCESSES_MESSAGES) {
+        Some(Limit::Processes)
+    } else {
+        None
+    }
+}
+
+/// Signals that say a run was stopped for its resource use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signal {
+    /// `SIGXCPU`, sent at the soft CPU limit
+    CpuLimit,
+    /// `SIGKILL`, sent at the hard CPU limit among others
+    Kill,
+}
+
+#[cfg(unix)]
+fn exit_signal(status: ExitStatus) -> Option<Signal> {
+    use std::os::unix::process::ExitStatusExt;
+    match status.signal()? {
+        libc::SIGXCPU => Some(Signal::CpuLimit),
+        libc::SIGKILL => Some(Signal::Kill),
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: ExitStatus) -> Option<Signal> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exceeded() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let limits = ResourceLimits {
+            memory_mb: Some(64),
+            cpu_seconds: Some(10),
+            open_files: Some(16),
+            ..Default::default()
+        };
+        let abort = ExitStatus::from_raw(libc::SIGABRT);
+        let xcpu = ExitStatus::from_raw(libc::SIGXCPU);
+        let exit_1 = ExitStatus::from_raw(1 << 8);
+
+        let kill = ExitStatus::from_raw(libc::SIGKILL);
+
+        let oom = b"memory allocation of 1048576 bytes failed";
+        assert_eq!(exceeded(limits, abort, oom, None), Some(Limit::Memory));
+        assert_eq!(exceeded(limits, xcpu, b"", None), Some(Limit::Cpu));
+
+        // Killed without a recognizable message, which may not be a limit
+        assert_eq!(exceeded(limits, kill, b"", None), None);
+        assert_eq!(exceeded(limits, kill, oom, None), Some(Limit::Memory));
+        let java = b"Exception in thread \"main\" java.lang.OutOfMemoryError: Java heap space\n\
+            \tat Main.parse(Main.java:12)\n\
+            \tat Main.main(Main.java:4)\n";
+        assert_eq!(exceeded(limits, exit_1, java, None), Some(Limit::Memory));
+        let files = b"Error: Too many open files (os error 24)";
+        assert_eq!(
+            exceeded(limits, exit_1, files, None),
+            Some(Limit::OpenFiles)
+        );
+
+        // Killed at the hard CPU limit, rather than merely running long
+        let usage = ResourceUsage {
+            user_time: Duration::from_secs(11),
+            ..Default::default()
+        };
+        assert_eq!(exceeded(limits, kill, b"", Some(usage)), Some(Limit::Cpu));
+        assert_eq!(exceeded(limits, exit_1, b"", Some(usage)), None);
+        let usage = ResourceUsage {
+            user_time: Duration::from_secs(10),
+            ..Default::default()
+        };
+        assert_eq!(exceeded(limits, kill, b"", Some(usage)), None);
+
+        // Unrelated failures, successes, and limits that weren't set
+        assert_eq!(exceeded(limits, exit_1, b"oops", None), None);
+        let logged =
+            b"warning: out of memory for cache, continuing\n1\n2\n3\n4\n5\nerror: bad input";
+        assert_eq!(exceeded(limits, exit_1, logged, None), None);
+        let unlimited = ResourceLimits {
+            memory_mb: None,
+            ..limits
+        };
+        assert_eq!(exceeded(unlimited, kill, oom, None), None);
+        assert_eq!(exceeded(limits, ExitStatus::from_raw(0), oom, None), None);
+        let thread = b"failed to spawn thread: Resource temporarily unavailable";
+        assert_eq!(exceeded(limits, abort, thread, None), None);
+        assert_eq!(exceeded(ResourceLimits::default(), abort, oom, None), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_apply() {
+        let limits = ResourceLimits {
+            memory_mb: Some(256),
+            open_files: Some(32),
+            ..Default::default()
+        };
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "ulimit -v; ulimit -n"]);
+        apply(&mut cmd, limits);
+
+        let output = cmd.output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "262144\n32\n");
+    }
+}
@@ -1,6 +1,16 @@
+pub mod capture;
 pub mod common;
 pub mod config;
 pub mod corpus;
 pub mod executor;
 pub mod implementation;
+pub mod isolation;
+pub mod jobs;
+pub mod limits;
+pub mod output;
+pub mod results;
+pub mod rusage;
 pub mod savefile;
+pub mod server;
+pub mod stats;
+pub mod version;
@@ -0,0 +1,158 @@
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Output written by an implementation using version 2 of the protocol: a
+/// single JSON object with either a result or an error.
+///
+/// ```json
+/// {"elapsed_us": 1500, "result": 3, "phases": [{"name": "parse", "elapsed_us": 1200}]}
+/// {"elapsed_us": 20, "error": {"kind": "rejected", "message": "unexpected }"}}
+/// ```
+#[derive(Debug, Deserialize)]
+struct OutputV2 {
+    /// Self reported time spent on the task in microseconds
+    elapsed_us: u64,
+    result: Option<serde_json::Value>,
+    error: Option<OutputError>,
+    #[serde(default)]
+    warnings: Vec<String>,
+    /// Time spent in each phase of the task, in the order performed
+    #[serde(default)]
+    phases: Vec<Phase>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Phase {
+    name: String,
+    elapsed_us: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct OutputError {
+    kind: OutputErrorKind,
+    #[serde(default)]
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputErrorKind {
+    /// The input is invalid
+    Rejected,
+    /// The implementation failed to complete the task
+    Failed,
+}
+
+/// A validated version 2 output
+#[derive(Debug, PartialEq)]
+pub enum ParsedOutput {
+    Success {
+        elapsed: Duration,
+        /// The result as text: strings verbatim, other values as JSON
+        output: String,
+        warnings: Vec<String>,
+        phases: Vec<(String, Duration)>,
+    },
+    Error {
+        kind: OutputErrorKind,
+        message: String,
+    },
+}
+
+/// Parse and validate version 2 output, explaining what is wrong with output
+/// that doesn't follow the protocol
+pub fn parse_v2(stdout: &[u8]) -> Result<ParsedOutput, String> {
+    let output: OutputV2 =
+        serde_json::from_slice(stdout).map_err(|e| format!("Invalid JSON output: {}", e))?;
+
+    let phases = output
+        .phases
+        .into_iter()
+        .map(|phase| (phase.name, Duration::from_micros(phase.elapsed_us)))
+        .collect();
+
+    match (output.result, output.error) {
+        (Some(result), None) => Ok(ParsedOutput::Success {
+            elapsed: Duration::from_micros(output.elapsed_us),
+            output: match result {
+                serde_json::Value::String(result) => result,
+                result => result.to_string(),
+            },
+            warnings: output.warnings,
+            phases,
+        }),
+        (None, Some(error)) => Ok(ParsedOutput::Error {
+            kind: error.kind,
+            message: error.message,
+        }),
+        (Some(_), Some(_)) => Err(String::from("Output has both a result and an error")),
+        (None, None) => Err(String::from("Output has neither a result nor an error")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_v2() {
+        let parsed = parse_v2(
+            br#"{
+                "elapsed_us": 15,
+                "result": {"a": [1, 2]},
+                "warnings": ["slow"],
+                "phases": [
+                    {"name": "tokenize", "elapsed_us": 5},
+                    {"name": "parse", "elapsed_us": 10}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed,
+            ParsedOutput::Success {
+                elapsed: Duration::from_micros(15),
+                output: String::from(r#"{"a":[1,2]}"#),
+                warnings: vec![String::from("slow")],
+                phases: vec![
+                    (String::from("tokenize"), Duration::from_micros(5)),
+                    (String::from("parse"), Duration::from_micros(10)),
+                ],
+            }
+        );
+
+        // Strings are used verbatim so multi-line results survive
+        let parsed = parse_v2(br#"{"elapsed_us": 1, "result": "a\nb"}"#).unwrap();
+        assert!(matches!(parsed, ParsedOutput::Success { output, .. } if output == "a\nb"));
+
+        let parsed =
+            parse_v2(br#"{"elapsed_us": 1, "error": {"kind": "rejected", "message": "bad"}}"#)
+                .unwrap();
+        assert_eq!(
+            parsed,
+            ParsedOutput::Error {
+                kind: OutputErrorKind::Rejected,
+                message: String::from("bad"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_v2_invalid() {
+        for output in [
+            &b"15\n3"[..],
+            br#"{"result": 3}"#,
+            br#"{"elapsed_us": -1, "result": 3}"#,
+            br#"{"elapsed_us": 1}"#,
+            br#"{"elapsed_us": 1, "result": 3, "error": {"kind": "failed"}}"#,
+            br#"{"elapsed_us": 1, "error": {"kind": "crashed"}}"#,
+            br#"{"elapsed_us": 1, "result": 3, "phases": [{"name": "parse", "elapsed_us": 1.5}]}"#,
+        ] {
+            assert!(
+                parse_v2(output).is_err(),
+                "{}",
+                String::from_utf8_lossy(output)
+            );
+        }
+    }
+}
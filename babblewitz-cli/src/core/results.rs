@@ -0,0 +1,100 @@
+use crate::core::config::TaskType;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Version of the results file format, increased on incompatible changes
+const RESULTS_VERSION: u32 = 2;
+
+/// A results file: a task's results, versioned so that files written by
+/// other versions of babblewitz are recognized
+#[derive(Serialize, Deserialize)]
+struct ResultsFile<T> {
+    version: u32,
+    task: TaskType,
+    #[serde(flatten)]
+    results: T,
+}
+
+/// Write the task's results to a JSON results file
+pub fn save<T: Serialize>(path: &Path, task: TaskType, results: &T) -> Result<()> {
+    let file = ResultsFile {
+        version: RESULTS_VERSION,
+        task,
+        results,
+    };
+    let json = serde_json::to_string_pretty(&file)?;
+    std::fs::write(path, json)
+        .with_context(|| format!("Failed to write results to {}", path.display()))
+}
+
+/// Read a results file written by [`save`], which must hold results of one
+/// of the given tasks
+pub fn load<T: DeserializeOwned>(path: &Path, tasks: &[TaskType]) -> Result<T> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read results from {}", path.display()))?;
+
+    #[derive(Deserialize)]
+    struct Header {
+        version: u32,
+        task: TaskType,
+    }
+    let header: Header = serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse results from {}", path.display()))?;
+    if header.version != RESULTS_VERSION {
+        anyhow::bail!(
+            "Results in {} are version {}, expected version {}",
+            path.display(),
+            header.version,
+            RESULTS_VERSION
+        );
+    }
+    if !tasks.contains(&header.task) {
+        anyhow::bail!(
+            "Results in {} are for the {} task, not {}",
+            path.display(),
+            header.task,
+            tasks
+                .iter()
+                .map(TaskType::as_str)
+                .collect::<Vec<_>>()
+                .join(" or ")
+        );
+    }
+
+    let file: ResultsFile<T> = serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse results from {}", path.display()))?;
+    Ok(file.results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Results {
+            values: Vec<u32>,
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.json");
+        let results = Results { values: vec![1, 2] };
+        save(&path, TaskType::CanParse, &results).unwrap();
+
+        let tasks = [TaskType::CanParse, TaskType::Localisation];
+        assert_eq!(load::<Results>(&path, &tasks).unwrap(), results);
+
+        let error = load::<Results>(&path, &[TaskType::Deserialization])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("for the can-parse task"), "{}", error);
+
+        let json = r#"{"version": 99, "task": "can-parse", "values": []}"#;
+        std::fs::write(&path, json).unwrap();
+        let error = load::<Results>(&path, &tasks).unwrap_err().to_string();
+        assert!(error.contains("version 99"), "{}", error);
+    }
+}
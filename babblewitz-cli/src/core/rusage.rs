@@ -0,0 +1,130 @@
+use std::process::{Child, ExitStatus};
+use std::time::Duration;
+
+/// Resources consumed by an implementation's process (and any descendants it
+/// waited on) as reported by the operating system
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ResourceUsage {
+    /// Peak resident set size
+    pub max_rss_bytes: u64,
+    pub user_time: Duration,
+    pub system_time: Duration,
+    /// Page faults that required I/O
+    pub major_faults: u64,
+    /// Page faults serviced without I/O
+    pub minor_faults: u64,
+    pub voluntary_context_switches: u64,
+    pub involuntary_context_switches: u64,
+}
+
+impl ResourceUsage {
+    /// Total CPU time spent in user and kernel mode
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.system_time
+    }
+
+    /// Combine the usage of several runs: the largest peak memory and the
+    /// sum of everything else
+    pub fn combine(&self, other: &ResourceUsage) -> ResourceUsage {
+        ResourceUsage {
+            max_rss_bytes: self.max_rss_bytes.max(other.max_rss_bytes),
+            user_time: self.user_time + other.user_time,
+            system_time: self.system_time + other.system_time,
+            major_faults: self.major_faults + other.major_faults,
+            minor_faults: self.minor_faults + other.minor_faults,
+            voluntary_context_switches: self.voluntary_context_switches
+                + other.voluntary_context_switches,
+            involuntary_context_switches: self.involuntary_context_switches
+                + other.involuntary_context_switches,
+        }
+    }
+}
+
+/// Wait for the child to exit, collecting its resource usage where the
+/// platform supports it
+#[cfg(target_os = "linux")]
+pub fn wait(child: Child) -> std::io::Result<(ExitStatus, Option<ResourceUsage>)> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = child.id() as libc::pid_t;
+    let mut status = 0;
+
+    // SAFETY: rusage is plain old data, so all zeroes is a valid value
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: the pointers are valid for the duration of the call, and
+        // the child hasn't been reaped as std only reaps in `wait`
+        let result = unsafe { libc::wait4(pid, &mut status, 0, &mut usage) };
+        if result == pid {
+            break;
+        }
+
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    let timeval = |tv: libc::timeval| {
+        Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+    };
+
+    let usage = ResourceUsage {
+        // Linux reports the peak in kilobytes
+        max_rss_bytes: usage.ru_maxrss as u64 * 1024,
+        user_time: timeval(usage.ru_utime),
+        system_time: timeval(usage.ru_stime),
+        major_faults: usage.ru_majflt as u64,
+        minor_faults: usage.ru_minflt as u64,
+        voluntary_context_switches: usage.ru_nvcsw as u64,
+        involuntary_context_switches: usage.ru_nivcsw as u64,
+    };
+
+    Ok((ExitStatus::from_raw(status), Some(usage)))
+}
+
+/// Wait for the child to exit, collecting its resource usage where the
+/// platform supports it
+#[cfg(not(target_os = "linux"))]
+pub fn wait(mut child: Child) -> std::io::Result<(ExitStatus, Option<ResourceUsage>)> {
+    Ok((child.wait()?, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine() {
+        let a = ResourceUsage {
+            max_rss_bytes: 100,
+            user_time: Duration::from_millis(5),
+            minor_faults: 3,
+            ..Default::default()
+        };
+        let b = ResourceUsage {
+            max_rss_bytes: 50,
+            user_time: Duration::from_millis(7),
+            system_time: Duration::from_millis(1),
+            minor_faults: 4,
+            ..Default::default()
+        };
+
+        let combined = a.combine(&b);
+        assert_eq!(combined.max_rss_bytes, 100);
+        assert_eq!(combined.cpu_time(), Duration::from_millis(13));
+        assert_eq!(combined.minor_faults, 7);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_wait_collects_usage() {
+        let child = std::process::Command::new("sh")
+            .args(["-c", "exit 3"])
+            .spawn()
+            .unwrap();
+        let (status, usage) = wait(child).unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert!(usage.unwrap().max_rss_bytes > 0);
+    }
+}
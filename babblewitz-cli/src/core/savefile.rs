@@ -82,10 +82,29 @@ impl SaveFile {
     pub fn read(&self) -> Result<Vec<u8>> {
         read_save_content(&self.file_path)
     }
+
+    /// The save's path when the file holds the text itself, rather than a zip
+    /// that the text is extracted from
+    pub fn text_path(&self) -> Option<&Path> {
+        let file = std::fs::File::open(&self.file_path).ok()?;
+        let mut buf = vec![0u8; rawzip::RECOMMENDED_BUFFER_SIZE];
+        match rawzip::ZipArchive::from_file(file, &mut buf) {
+            Ok(_) => None,
+            Err(_) => Some(&self.file_path),
+        }
+    }
+
+    /// The save's file name for display
+    pub fn file_name(&self) -> String {
+        self.file_path
+            .file_name()
+            .map_or_else(String::new, |x| x.to_string_lossy().into_owned())
+    }
 }
 
 pub fn find_save_files<P: AsRef<Path>>(corpus_path: P) -> impl Iterator<Item = SaveFile> {
     WalkDir::new(corpus_path.as_ref())
+        .sort_by_file_name()
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|entry| entry.file_type().is_file())
@@ -173,6 +192,12 @@ mod tests {
 
         let result = read_save_content(&file_path).unwrap();
         assert_eq!(result, content);
+
+        let save = SaveFile {
+            file_path: file_path.clone(),
+            detected_game: Game::Eu4,
+        };
+        assert_eq!(save.text_path(), Some(file_path.as_path()));
     }
 
     #[test]
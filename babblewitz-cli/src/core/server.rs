@@ -0,0 +1,277 @@
+use crate::core::capture::CappedBuffer;
+use crate::core::config::TaskType;
+use crate::core::corpus::Encoding;
+use crate::core::executor::kill_process_group;
+use crate::core::savefile::Game;
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::process::{Child, ChildStdin, Command, ExitStatus};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// A request to an implementation running in server mode. It is framed as a
+/// header line of space separated `key=value` fields followed by the payload:
+///
+/// ```text
+/// task=can-parse game=eu4 game=ck3 encoding=windows-1252 length=11
+/// foo=bar
+/// a=b
+/// ```
+pub struct Request<'a> {
+    pub task: TaskType,
+    pub games: &'a [Game],
+    pub encoding: Option<Encoding>,
+    pub payload: &'a [u8],
+}
+
+impl Request<'_> {
+    fn header(&self) -> String {
+        let mut fields = vec![format!("task={}", self.task)];
+        fields.extend(self.games.iter().map(|game| format!("game={}", game)));
+        if let Some(encoding) = self.encoding {
+            fields.push(format!("encoding={}", encoding));
+        }
+        fields.push(format!("length={}", self.payload.len()));
+        fields.join(" ")
+    }
+}
+
+/// A response from an implementation running in server mode, framed as a
+/// status line of `ok <length>` or `error <length>` followed by the body. An
+/// `ok` body is the same output a process writes: elapsed microseconds, then
+/// the result.
+#[derive(Debug, PartialEq)]
+pub enum Response {
+    Ok(Vec<u8>),
+    Error(Vec<u8>),
+}
+
+/// Read a response, refusing bodies over `max_body` bytes rather than
+/// allocating whatever length the server claims
+fn read_response(reader: &mut impl BufRead, max_body: usize) -> std::io::Result<Response> {
+    use std::io::Error;
+
+    let mut header = String::new();
+    if reader.read_line(&mut header)? == 0 {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "server closed its output",
+        ));
+    }
+
+    let invalid = || {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("malformed response header: '{}'", header.trim_end()),
+        )
+    };
+
+    let (status, length) = header.trim_end().split_once(' ').ok_or_else(invalid)?;
+    let length: usize = length.parse().map_err(|_| invalid())?;
+    if length > max_body {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "response of {} bytes exceeds the limit of {} bytes",
+                length, max_body
+            ),
+        ));
+    }
+
+    let mut body = Vec::with_capacity(length);
+    reader.take(length as u64).read_to_end(&mut body)?;
+    if body.len() < length {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "server closed its output mid-response",
+        ));
+    }
+
+    match status {
+        "ok" => Ok(Response::Ok(body)),
+        "error" => Ok(Response::Error(body)),
+        _ => Err(invalid()),
+    }
+}
+
+/// What came of sending a request to a server
+#[derive(Debug)]
+pub enum Reply {
+    Response(Response),
+    /// No response arrived within the timeout, and the server was killed
+    TimedOut,
+    /// The server exited (or closed its output) before responding
+    Exited {
+        status: ExitStatus,
+        stderr: String,
+    },
+    /// The server's response didn't follow the protocol, and it was killed
+    Malformed(String),
+}
+
+/// A long running implementation process that serves requests one at a time
+pub struct ServerProcess {
+    child: Child,
+    stdin: ChildStdin,
+    responses: Receiver<std::io::Result<Response>>,
+    stderr: Arc<Mutex<CappedBuffer>>,
+    stderr_reader: Option<JoinHandle<()>>,
+    /// Whether the server was killed and can't serve further requests
+    stopped: bool,
+}
+
+impl ServerProcess {
+    /// Spawn the server, accepting responses of up to `max_output` bytes and
+    /// keeping as much of its stderr. The command must have piped stdio.
+    pub fn spawn(cmd: &mut Command, max_output: usize) -> Result<Self> {
+        let mut child = cmd.spawn().context("Failed to start server")?;
+        let stdin = child.stdin.take().context("Server stdin is not piped")?;
+        let stdout = child.stdout.take().context("Server stdout is not piped")?;
+        let mut stderr_pipe = child.stderr.take().context("Server stderr is not piped")?;
+
+        let (sender, responses) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            loop {
+                let response = read_response(&mut reader, max_output);
+                let failed = response.is_err();
+                if sender.send(response).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        // Drain stderr so the server can't block on it, keeping it to explain crashes
+        let stderr = Arc::new(Mutex::new(CappedBuffer::new(max_output)));
+        let sink = Arc::clone(&stderr);
+        let stderr_reader = std::thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            while let Ok(n @ 1..) = stderr_pipe.read(&mut buffer) {
+                sink.lock().unwrap().extend(&buffer[..n]);
+            }
+        });
+
+        Ok(ServerProcess {
+            child,
+            stdin,
+            responses,
+            stderr,
+            stderr_reader: Some(stderr_reader),
+            stopped: false,
+        })
+    }
+
+    /// Whether the server was stopped by a request and can't serve another
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Send a request and wait for its response. The server is stopped unless
+    /// a response arrives and the whole request was read within the timeout,
+    /// and should then be dropped (see [`ServerProcess::is_stopped`]).
+    pub fn request(&mut self, request: &Request, timeout: Duration) -> Result<Reply> {
+        let ServerProcess {
+            child,
+            stdin,
+            responses,
+            stderr,
+            stderr_reader,
+            stopped,
+        } = self;
+
+        let start = Instant::now();
+        let (written, wrote) = mpsc::channel();
+        let response = std::thread::scope(|scope| {
+            // Write on another thread so a server that stops reading can't
+            // block us past the timeout. Write errors surface as the server
+            // closing its output.
+            scope.spawn(move || {
+                let result = writeln!(stdin, "{}", request.header())
+                    .and_then(|_| stdin.write_all(request.payload))
+                    .and_then(|_| stdin.flush());
+                let _ = written.send(result.is_ok());
+            });
+
+            // A server may respond before reading all of a large payload,
+            // leaving the writer blocked until the rest is read
+            let response = responses.recv_timeout(timeout);
+            let finished = matches!(response, Ok(Ok(_)))
+                && wrote
+                    .recv_timeout(timeout.saturating_sub(start.elapsed()))
+                    .unwrap_or(false);
+            if !finished {
+                // Unblock the writer
+                kill_process_group(child.id());
+                *stopped = true;
+            }
+            response
+        });
+
+        match response {
+            Ok(Ok(response)) => Ok(Reply::Response(response)),
+            Err(RecvTimeoutError::Timeout) => Ok(Reply::TimedOut),
+            Ok(Err(e)) if e.kind() == ErrorKind::InvalidData => Ok(Reply::Malformed(e.to_string())),
+            Ok(Err(_)) => {
+                // The server has normally exited by the time its output
+                // closes, so killing it above doesn't change its status
+                let status = child.wait()?;
+                if let Some(reader) = stderr_reader.take() {
+                    let _ = reader.join();
+                }
+                let stderr = std::mem::replace(&mut *stderr.lock().unwrap(), CappedBuffer::new(0));
+                let stderr = String::from_utf8_lossy(&stderr.into_bytes()).into_owned();
+                Ok(Reply::Exited { status, stderr })
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(anyhow::anyhow!("Server reader exited")),
+        }
+    }
+}
+
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        kill_process_group(self.child.id());
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_header() {
+        let request = Request {
+            task: TaskType::CanParse,
+            games: &[Game::Eu4, Game::Ck3],
+            encoding: Some(Encoding::Windows1252),
+            payload: b"a=b",
+        };
+        assert_eq!(
+            request.header(),
+            "task=can-parse game=eu4 game=ck3 encoding=windows-1252 length=3"
+        );
+    }
+
+    #[test]
+    fn test_read_response() {
+        let mut reader = &b"ok 4\n15\n3error 5\noops!"[..];
+        assert_eq!(
+            read_response(&mut reader, 16).unwrap(),
+            Response::Ok(b"15\n3".to_vec())
+        );
+        assert_eq!(
+            read_response(&mut reader, 16).unwrap(),
+            Response::Error(b"oops!".to_vec())
+        );
+        assert!(read_response(&mut reader, 16).is_err());
+
+        assert!(read_response(&mut &b"ok\n"[..], 16).is_err());
+        assert!(read_response(&mut &b"ok 10\nshort"[..], 16).is_err());
+        assert!(read_response(&mut &b"maybe 1\nx"[..], 16).is_err());
+
+        let error = read_response(&mut &b"ok 17\n"[..], 16).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
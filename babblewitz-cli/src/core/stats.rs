@@ -0,0 +1,267 @@
+/// Summary statistics of a set of samples
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct Summary {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    /// Sample standard deviation, zero for a single sample
+    pub stddev: f64,
+}
+
+impl Summary {
+    /// Summarize the samples, or `None` when there are none
+    pub fn of(samples: &[f64]) -> Option<Summary> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let sorted = sorted(samples);
+        let count = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / count as f64;
+        let variance = if count > 1 {
+            sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (count - 1) as f64
+        } else {
+            0.0
+        };
+
+        Some(Summary {
+            count,
+            min: sorted[0],
+            max: sorted[count - 1],
+            mean,
+            median: percentile_of_sorted(&sorted, 50.0),
+            stddev: variance.sqrt(),
+        })
+    }
+}
+
+/// The value below which the given percent of the samples fall, interpolating
+/// between the closest samples. Returns `None` when there are no samples.
+pub fn percentile(samples: &[f64], percent: f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    Some(percentile_of_sorted(&sorted(samples), percent))
+}
+
+/// Result of a two-sided Mann-Whitney U test of whether one set of samples
+/// tends to be larger than the other
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct MannWhitney {
+    /// U statistic of the first set of samples
+    pub u: f64,
+    /// Probability of a difference at least this large if both sets of
+    /// samples came from the same distribution
+    pub p_value: f64,
+}
+
+/// Test whether the samples differ with the Mann-Whitney U test, using the
+/// normal approximation with corrections for ties and continuity. Returns
+/// `None` if either set is empty.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> Option<MannWhitney> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    // Rank the combined samples, giving tied samples their average rank
+    let mut combined: Vec<(f64, bool)> = a
+        .iter()
+        .map(|x| (*x, true))
+        .chain(b.iter().map(|x| (*x, false)))
+        .collect();
+    combined.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    let mut rank_sum_a = 0.0;
+    let mut tie_term = 0.0;
+    let mut start = 0;
+    while start < combined.len() {
+        let end = start
+            + combined[start..]
+                .iter()
+                .take_while(|x| x.0 == combined[start].0)
+                .count();
+        let ties = (end - start) as f64;
+        let rank = (start + end + 1) as f64 / 2.0;
+        rank_sum_a += rank * combined[start..end].iter().filter(|x| x.1).count() as f64;
+        tie_term += ties.powi(3) - ties;
+        start = end;
+    }
+
+    let n_a = a.len() as f64;
+    let n_b = b.len() as f64;
+    let n = n_a + n_b;
+    let u = rank_sum_a - n_a * (n_a + 1.0) / 2.0;
+    let mean = n_a * n_b / 2.0;
+    let variance = n_a * n_b / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        // Every sample is the same
+        return Some(MannWhitney { u, p_value: 1.0 });
+    }
+
+    let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    let p_value = (2.0 * (1.0 - normal_cdf(z))).min(1.0);
+    Some(MannWhitney { u, p_value })
+}
+
+/// Confidence interval of the ratio of the medians of `b` to `a`, from
+/// bootstrap resamples of both. Resampling is seeded so that the same samples
+/// always give the same interval. Returns `None` if either set is empty.
+pub fn bootstrap_median_ratio(
+    a: &[f64],
+    b: &[f64],
+    confidence: f64,
+    resamples: usize,
+) -> Option<(f64, f64)> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    let mut rng = SplitMix64(0x6261_6262_6c65_7769);
+    let mut resample = |samples: &[f64], buffer: &mut Vec<f64>| {
+        buffer.clear();
+        buffer.extend((0..samples.len()).map(|_| samples[rng.below(samples.len())]));
+        percentile(buffer, 50.0).unwrap()
+    };
+
+    let mut buffer = Vec::new();
+    let mut ratios = Vec::with_capacity(resamples);
+    for _ in 0..resamples {
+        let median_a = resample(a, &mut buffer);
+        let median_b = resample(b, &mut buffer);
+        if median_a > 0.0 {
+            ratios.push(median_b / median_a);
+        }
+    }
+
+    let tail = (1.0 - confidence) / 2.0 * 100.0;
+    Some((
+        percentile(&ratios, tail)?,
+        percentile(&ratios, 100.0 - tail)?,
+    ))
+}
+
+/// Standard normal cumulative distribution function
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+/// Error function, accurate to about 1.5e-7 (Abramowitz and Stegun 7.1.26)
+fn erf(x: f64) -> f64 {
+    let sign = x.signum();
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    sign * (1.0 - poly * (-x * x).exp())
+}
+
+/// Small deterministic random number generator for resampling
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+fn sorted(samples: &[f64]) -> Vec<f64> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+fn percentile_of_sorted(sorted: &[f64], percent: f64) -> f64 {
+    let rank = (percent.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let fraction = rank - lower as f64;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let summary = Summary::of(&[4.0, 1.0, 3.0, 2.0]).unwrap();
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 4.0);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.median, 2.5);
+        assert!((summary.stddev - 1.2910).abs() < 1e-4);
+
+        let single = Summary::of(&[7.0]).unwrap();
+        assert_eq!(single.median, 7.0);
+        assert_eq!(single.stddev, 0.0);
+
+        assert_eq!(Summary::of(&[]), None);
+    }
+
+    #[test]
+    fn test_mann_whitney_u() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+        let b = [9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0];
+        let result = mann_whitney_u(&a, &b).unwrap();
+        assert_eq!(result.u, 0.0);
+        assert!(result.p_value < 0.001, "{:?}", result);
+
+        // Interleaved samples are indistinguishable
+        let a = [1.0, 3.0, 5.0, 7.0];
+        let b = [2.0, 4.0, 6.0, 8.0];
+        let result = mann_whitney_u(&a, &b).unwrap();
+        assert_eq!(result.u, 6.0);
+        assert!(result.p_value > 0.5, "{:?}", result);
+
+        let result = mann_whitney_u(&[1.0, 1.0], &[1.0, 1.0]).unwrap();
+        assert_eq!(result.p_value, 1.0);
+        assert_eq!(mann_whitney_u(&[], &[1.0]), None);
+    }
+
+    #[test]
+    fn test_bootstrap_median_ratio() {
+        let a: Vec<f64> = (0..30).map(|x| 100.0 + f64::from(x % 5)).collect();
+        let b: Vec<f64> = a.iter().map(|x| x * 1.5).collect();
+        let (low, high) = bootstrap_median_ratio(&a, &b, 0.95, 1000).unwrap();
+        assert!(low <= 1.5 && 1.5 <= high, "{} {}", low, high);
+        assert!(low > 1.4 && high < 1.6, "{} {}", low, high);
+
+        // Seeded, so the interval is reproducible
+        assert_eq!(
+            bootstrap_median_ratio(&a, &b, 0.95, 1000),
+            Some((low, high))
+        );
+        assert_eq!(bootstrap_median_ratio(&a, &[], 0.95, 1000), None);
+    }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-4);
+        assert!((normal_cdf(-1.96) - 0.025).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let samples: Vec<f64> = (1..=101).map(f64::from).collect();
+        assert_eq!(percentile(&samples, 95.0), Some(96.0));
+        assert_eq!(percentile(&samples, 5.0), Some(6.0));
+        assert_eq!(percentile(&[1.0, 2.0], 50.0), Some(1.5));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+}
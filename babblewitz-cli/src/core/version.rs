@@ -0,0 +1,186 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+
+/// A game patch version (eg: `1.9` or `1.37.2`). Versions compare component
+/// by component with missing components treated as zero, so `1.9` and `1.9.0`
+/// are equal.
+#[derive(Debug, Clone)]
+pub struct GameVersion(Vec<u32>);
+
+impl GameVersion {
+    pub fn from_str(s: &str) -> Option<Self> {
+        let components = s
+            .split('.')
+            .map(|component| component.parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        Some(GameVersion(components))
+    }
+
+    fn component(&self, index: usize) -> u32 {
+        self.0.get(index).copied().unwrap_or(0)
+    }
+}
+
+impl PartialEq for GameVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for GameVersion {}
+
+impl PartialOrd for GameVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GameVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.0.len().max(other.0.len());
+        (0..len)
+            .map(|i| self.component(i).cmp(&other.component(i)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl std::fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let components: Vec<String> = self.0.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", components.join("."))
+    }
+}
+
+/// An inclusive range of game versions, written as `1.5..1.9` where either
+/// end may be omitted (`..1.8`, `1.5..`). A single version (`1.8`) is a range
+/// of just that version.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionRange {
+    pub min: Option<GameVersion>,
+    pub max: Option<GameVersion>,
+}
+
+impl VersionRange {
+    pub fn from_str(s: &str) -> Option<Self> {
+        let bound = |x: &str| -> Option<Option<GameVersion>> {
+            let x = x.trim();
+            if x.is_empty() {
+                Some(None)
+            } else {
+                GameVersion::from_str(x).map(Some)
+            }
+        };
+
+        match s.split_once("..") {
+            Some((min, max)) => {
+                let range = VersionRange {
+                    min: bound(min)?,
+                    max: bound(max)?,
+                };
+                match (&range.min, &range.max) {
+                    (Some(min), Some(max)) if min > max => None,
+                    _ => Some(range),
+                }
+            }
+            None => {
+                let version = GameVersion::from_str(s.trim())?;
+                Some(VersionRange {
+                    min: Some(version.clone()),
+                    max: Some(version),
+                })
+            }
+        }
+    }
+
+    /// Whether the range reaches the given version or any later one
+    pub fn reaches(&self, version: &GameVersion) -> bool {
+        self.max.as_ref().is_none_or(|max| max >= version)
+    }
+}
+
+impl std::fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.min, &self.max) {
+            (Some(min), Some(max)) if min == max => write!(f, "{}", min),
+            (min, max) => {
+                if let Some(min) = min {
+                    write!(f, "{}", min)?;
+                }
+                write!(f, "..")?;
+                if let Some(max) = max {
+                    write!(f, "{}", max)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Serialize for VersionRange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionRange {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        VersionRange::from_str(&s).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "invalid version range: '{}' (expected eg: 1.5..1.9)",
+                s
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(s: &str) -> GameVersion {
+        GameVersion::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_game_version_ordering() {
+        assert_eq!(version("1.9"), version("1.9.0"));
+        assert!(version("1.10") > version("1.9"));
+        assert!(version("1.9.1") > version("1.9"));
+        assert!(version("2") > version("1.37.2"));
+        assert_eq!(version("1.37.2").to_string(), "1.37.2");
+
+        assert!(GameVersion::from_str("").is_none());
+        assert!(GameVersion::from_str("1.x").is_none());
+        assert!(GameVersion::from_str("1..2").is_none());
+    }
+
+    #[test]
+    fn test_version_range() {
+        let range = VersionRange::from_str("..1.8").unwrap();
+        assert_eq!(range.min, None);
+        assert!(range.reaches(&version("1.8")));
+        assert!(!range.reaches(&version("1.9")));
+        assert_eq!(range.to_string(), "..1.8");
+
+        let range = VersionRange::from_str("1.5..").unwrap();
+        assert!(range.reaches(&version("1.4")));
+        assert!(range.reaches(&version("9.0")));
+        assert_eq!(range.to_string(), "1.5..");
+
+        let range = VersionRange::from_str("1.8").unwrap();
+        assert_eq!(range.min, Some(version("1.8")));
+        assert_eq!(range.max, Some(version("1.8")));
+        assert_eq!(range.to_string(), "1.8");
+
+        assert_eq!(
+            VersionRange::from_str("1.5..1.9").unwrap().to_string(),
+            "1.5..1.9"
+        );
+        assert!(VersionRange::from_str("1.5...1.9").is_none());
+        assert!(VersionRange::from_str("latest").is_none());
+        assert!(VersionRange::from_str("1.9..1.5").is_none());
+        assert!(VersionRange::from_str("1.9..1.9").is_some());
+    }
+}
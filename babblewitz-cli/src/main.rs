@@ -1,5 +1,8 @@
+use crate::commands::baseline::{self, RegressionGate};
 use crate::commands::tasks::{can_parse, deserialization};
+use crate::commands::{compare, history};
 use clap::{Parser, Subcommand};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 mod commands;
@@ -23,18 +26,102 @@ enum TaskType {
         /// Output format (table, github)
         #[arg(long, default_value_t = Format::Table)]
         format: Format,
+        /// Group results by game or by syntax feature
+        #[arg(long, default_value_t = can_parse::Pivot::Game)]
+        pivot: can_parse::Pivot,
+        /// Number of corpus files to run at once (defaults to the number of CPUs)
+        #[arg(short, long)]
+        jobs: Option<NonZeroUsize>,
+        /// Write the success rates and failed corpus files to a JSON results file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Results file of an earlier run to check this run against
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+        /// Fail if a game's success rate dropped or a corpus file fails that
+        /// didn't in the baseline
+        #[arg(long, requires = "baseline")]
+        no_conformance_regression: bool,
+        /// Don't record this run in the history
+        #[arg(long)]
+        no_history: bool,
+    },
+    /// Run localisation conformance tests
+    Localisation {
+        /// Path to implementation directory (if omitted, runs against all impls)
+        #[arg(short, long)]
+        implementation: Option<PathBuf>,
+        /// Output format (table, github)
+        #[arg(long, default_value_t = Format::Table)]
+        format: Format,
+        /// Group results by game or by syntax feature
+        #[arg(long, default_value_t = can_parse::Pivot::Game)]
+        pivot: can_parse::Pivot,
+        /// Number of corpus files to run at once (defaults to the number of CPUs)
+        #[arg(short, long)]
+        jobs: Option<NonZeroUsize>,
+        /// Write the success rates and failed corpus files to a JSON results file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Results file of an earlier run to check this run against
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+        /// Fail if a game's success rate dropped or a corpus file fails that
+        /// didn't in the baseline
+        #[arg(long, requires = "baseline")]
+        no_conformance_regression: bool,
+        /// Don't record this run in the history
+        #[arg(long)]
+        no_history: bool,
     },
     /// Run deserialization performance tests
     Deserialization {
         /// Path to implementation directory (if omitted, runs against all impls)
         #[arg(short, long)]
         implementation: Option<PathBuf>,
+        /// Number of save files to run at once. Concurrent runs compete for
+        /// resources and skew timings, so benchmarks run one at a time by default.
+        #[arg(short, long, default_value = "1")]
+        jobs: NonZeroUsize,
+        /// Number of measured runs of each save file
+        #[arg(long, default_value = "1")]
+        iterations: NonZeroUsize,
+        /// Number of runs of each save file before the measured runs, to warm
+        /// up caches and runtimes
+        #[arg(long, default_value = "0")]
+        warmup: usize,
+        /// Include each game's average user and system CPU time per file in the table
+        #[arg(long)]
+        cpu: bool,
+        /// Clock that throughput is measured by
+        #[arg(long, default_value = "self-reported")]
+        clock: deserialization::Clock,
+        /// Write the measurements of every run to a JSON results file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Isolation mode: pin every run to this CPU and warn about system
+        /// load and frequency scaling (Linux only, can't be combined with --jobs)
+        #[arg(long, value_name = "CPU")]
+        isolate: Option<usize>,
+        /// Results file of an earlier run to check this run against
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+        /// Fail if a save file's median throughput dropped by more than this
+        /// percentage from the baseline (eg: 10%)
+        #[arg(long, requires = "baseline", value_parser = baseline::parse_percentage)]
+        max_slowdown: Option<f64>,
+        /// Fail if a save file that ran successfully in the baseline fails
+        #[arg(long, requires = "baseline")]
+        no_conformance_regression: bool,
+        /// Don't record this run in the history
+        #[arg(long)]
+        no_history: bool,
     },
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Run tasks (can-parse, deserialization)
+    /// Run tasks (can-parse, localisation, deserialization)
     Task {
         #[command(subcommand)]
         task_type: TaskType,
@@ -45,8 +132,59 @@ enum Commands {
         #[arg(short, long)]
         implementation: Option<PathBuf>,
     },
+    /// Compare the deserialization throughput of two implementations on the
+    /// same saves, with a confidence interval and significance test per game
+    Compare {
+        /// Baseline implementation directory or results file (use
+        /// `results.json#<name>` to choose one of several implementations)
+        baseline: compare::Source,
+        /// Candidate implementation directory or results file
+        candidate: compare::Source,
+        /// Number of measured runs of each save file for implementations
+        #[arg(long, default_value = "10")]
+        iterations: NonZeroUsize,
+        /// Number of runs of each save file before the measured runs
+        #[arg(long, default_value = "1")]
+        warmup: usize,
+        /// Clock that throughput is measured by
+        #[arg(long, default_value = "self-reported")]
+        clock: deserialization::Clock,
+        /// Confidence level of the intervals and significance test
+        #[arg(long, default_value = "0.95", value_parser = parse_confidence)]
+        confidence: f64,
+        /// Isolation mode: pin every run to this CPU and warn about system
+        /// load and frequency scaling (Linux only)
+        #[arg(long, value_name = "CPU")]
+        isolate: Option<usize>,
+    },
+    /// Show how throughput and conformance changed across recorded runs
+    History {
+        /// Only show this implementation
+        #[arg(short, long)]
+        implementation: Option<String>,
+        /// Only show this game
+        #[arg(long)]
+        game: Option<core::savefile::Game>,
+        /// Number of most recent runs to show of each implementation and game
+        #[arg(long, default_value = "10")]
+        limit: usize,
+        /// Include runs recorded on other machines
+        #[arg(long)]
+        all_machines: bool,
+    },
     /// Sync remote assets from S3, downloading if local files don't match
     SyncAssets,
+    /// Maintain the corpus
+    Corpus {
+        #[command(subcommand)]
+        command: CorpusCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum CorpusCommand {
+    /// Check corpus files for directive, numbering, and encoding problems
+    Lint,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
@@ -70,6 +208,81 @@ impl std::fmt::Display for Format {
     }
 }
 
+/// Parse a confidence level strictly between zero and one
+fn parse_confidence(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(x) if x > 0.0 && x < 1.0 => Ok(x),
+        _ => Err(String::from("must be a number between 0 and 1, like 0.95")),
+    }
+}
+
+/// Where the results of a task go besides the terminal
+struct ResultSinks<'a> {
+    /// JSON results file to write
+    output: Option<&'a std::path::Path>,
+    /// Baseline to check the results against
+    gate: Option<&'a RegressionGate>,
+    /// Whether to append the results to the history
+    history: bool,
+}
+
+/// Run a conformance task, print its results, and check them against the
+/// baseline
+fn run_conformance(
+    task: core::config::TaskType,
+    implementation: Option<&std::path::Path>,
+    format: Format,
+    pivot: can_parse::Pivot,
+    jobs: usize,
+    sinks: ResultSinks,
+) -> anyhow::Result<()> {
+    let table = match implementation {
+        Some(impl_path) => can_parse::run_conformance_tests(impl_path, task, jobs)?,
+        None => {
+            println!("Running {} tests across all implementations...", task);
+            can_parse::run_all_conformance(task, jobs)?
+        }
+    };
+
+    match format {
+        Format::Github => can_parse::print_github_summary(&table, pivot),
+        Format::Table => can_parse::print_can_parse_table(&table, pivot),
+    }
+    can_parse::print_outcome_details(&table);
+    can_parse::print_skipped_details(&table);
+    can_parse::print_warning_details(&table);
+    can_parse::print_failure_details(&table);
+    if sinks.history {
+        warn_unrecorded(history::record_conformance(
+            &history::default_path(),
+            task,
+            &table,
+            implementation,
+        ));
+    }
+
+    if let Some(path) = sinks.output {
+        table.save(path, task)?;
+    }
+    if let Some(gate) = sinks.gate {
+        baseline::check_conformance(gate, task, &table)?;
+    }
+    Ok(())
+}
+
+/// Report results that couldn't be recorded in the history without failing
+/// the run that produced them
+fn warn_unrecorded(result: anyhow::Result<()>) {
+    if let Err(e) = result {
+        eprintln!("Warning: results weren't recorded in the history: {:#}", e);
+    }
+}
+
+/// Number of jobs requested on the command line, else one per CPU
+fn resolve_jobs(jobs: Option<NonZeroUsize>) -> usize {
+    jobs.map_or_else(core::jobs::default_jobs, NonZeroUsize::get)
+}
+
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
@@ -78,38 +291,131 @@ fn main() -> anyhow::Result<()> {
             TaskType::CanParse {
                 implementation,
                 format,
-            } => match implementation {
-                Some(impl_path) => {
-                    let table = can_parse::run_can_parse_tests(&impl_path)?;
-                    match format {
-                        Format::Github => can_parse::print_github_summary(&table),
-                        Format::Table => can_parse::print_can_parse_table(&table),
+                pivot,
+                jobs,
+                output,
+                baseline,
+                no_conformance_regression,
+                no_history,
+            } => {
+                let gate = RegressionGate::from_args(
+                    core::config::TaskType::CanParse,
+                    baseline,
+                    None,
+                    no_conformance_regression,
+                )?;
+                run_conformance(
+                    core::config::TaskType::CanParse,
+                    implementation.as_deref(),
+                    format,
+                    pivot,
+                    resolve_jobs(jobs),
+                    ResultSinks {
+                        output: output.as_deref(),
+                        gate: gate.as_ref(),
+                        history: !no_history,
+                    },
+                )?;
+            }
+            TaskType::Localisation {
+                implementation,
+                format,
+                pivot,
+                jobs,
+                output,
+                baseline,
+                no_conformance_regression,
+                no_history,
+            } => {
+                let gate = RegressionGate::from_args(
+                    core::config::TaskType::Localisation,
+                    baseline,
+                    None,
+                    no_conformance_regression,
+                )?;
+                run_conformance(
+                    core::config::TaskType::Localisation,
+                    implementation.as_deref(),
+                    format,
+                    pivot,
+                    resolve_jobs(jobs),
+                    ResultSinks {
+                        output: output.as_deref(),
+                        gate: gate.as_ref(),
+                        history: !no_history,
+                    },
+                )?;
+            }
+            TaskType::Deserialization {
+                implementation,
+                jobs,
+                iterations,
+                warmup,
+                cpu,
+                clock,
+                output,
+                isolate,
+                baseline,
+                max_slowdown,
+                no_conformance_regression,
+                no_history,
+            } => {
+                let gate = RegressionGate::from_args(
+                    core::config::TaskType::Deserialization,
+                    baseline,
+                    max_slowdown,
+                    no_conformance_regression,
+                )?;
+                let options = deserialization::RunOptions {
+                    jobs: jobs.get(),
+                    iterations: iterations.get(),
+                    warmup,
+                    isolate_cpu: isolate,
+                };
+                match implementation {
+                    Some(impl_path) => {
+                        let results =
+                            deserialization::run_impl_benchmarks(&impl_path, options, clock)?;
+                        deserialization::print_benchmark_results(&results)?;
+                        let table = deserialization::PerformanceTable::from_results(&results);
+                        if !no_history {
+                            warn_unrecorded(history::record_benchmarks(
+                                &history::default_path(),
+                                &table,
+                                Some(&impl_path),
+                            ));
+                        }
+                        if let Some(path) = output {
+                            table.save(&path)?;
+                        }
+                        if let Some(gate) = &gate {
+                            baseline::check_benchmarks(gate, &table, clock)?;
+                        }
                     }
-
-                    can_parse::print_failure_details(&table);
-                }
-                None => {
-                    println!("Running can-parse tests across all implementations...");
-                    let table = can_parse::run_all_can_parse()?;
-                    match format {
-                        Format::Github => can_parse::print_github_summary(&table),
-                        Format::Table => can_parse::print_can_parse_table(&table),
+                    None => {
+                        println!(
+                            "Running deserialization benchmarks across all implementations..."
+                        );
+                        let table = deserialization::run_benchmark_table(options)?;
+                        deserialization::print_benchmark_table(&table, clock, cpu);
+                        deserialization::print_outcome_details(&table);
+                        deserialization::print_warnings(&table);
+                        if !no_history {
+                            warn_unrecorded(history::record_benchmarks(
+                                &history::default_path(),
+                                &table,
+                                None,
+                            ));
+                        }
+                        if let Some(path) = output {
+                            table.save(&path)?;
+                        }
+                        if let Some(gate) = &gate {
+                            baseline::check_benchmarks(gate, &table, clock)?;
+                        }
                     }
-
-                    can_parse::print_failure_details(&table);
-                }
-            },
-            TaskType::Deserialization { implementation } => match implementation {
-                Some(impl_path) => {
-                    let results = deserialization::run_impl_benchmarks(&impl_path)?;
-                    deserialization::print_benchmark_results(&results)?;
                 }
-                None => {
-                    println!("Running deserialization benchmarks across all implementations...");
-                    let table = deserialization::run_benchmark_table()?;
-                    deserialization::print_benchmark_table(&table);
-                }
-            },
+            }
         },
         Commands::Build { implementation } => match implementation {
             Some(impl_path) => {
@@ -121,9 +427,45 @@ fn main() -> anyhow::Result<()> {
                 commands::build::build_all_implementations()?;
             }
         },
+        Commands::Compare {
+            baseline,
+            candidate,
+            iterations,
+            warmup,
+            clock,
+            confidence,
+            isolate,
+        } => {
+            let options = deserialization::RunOptions {
+                jobs: 1,
+                iterations: iterations.get(),
+                warmup,
+                isolate_cpu: isolate,
+            };
+            let comparison =
+                compare::run_comparison(&baseline, &candidate, options, clock, confidence)?;
+            compare::print_comparison(&comparison);
+        }
+        Commands::History {
+            implementation,
+            game,
+            limit,
+            all_machines,
+        } => {
+            let filter = history::HistoryFilter {
+                implementation,
+                game,
+                all_machines,
+                limit,
+            };
+            history::print_history(&history::default_path(), &filter)?;
+        }
         Commands::SyncAssets => {
             commands::sync_assets::sync_assets()?;
         }
+        Commands::Corpus { command } => match command {
+            CorpusCommand::Lint => commands::corpus::lint::run_lint()?,
+        },
     }
 
     Ok(())
@@ -1,7 +1,10 @@
 use anyhow::Context;
 use clap::{Parser, ValueEnum};
-use jomini::{JominiDeserialize, TextDeserializer};
+use jomini::text::{Operator, ReaderError, Token, TokenReader};
+use jomini::{JominiDeserialize, TextDeserializer, Windows1252Encoding};
 use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::io::{Cursor, Read, Write};
 
 #[derive(Parser)]
@@ -13,6 +16,10 @@ struct Cli {
     /// Game tag associated with this content (can be specified multiple times)
     #[arg(short, long, action = clap::ArgAction::Append)]
     game: Vec<String>,
+    /// Encoding of the content when declared by the corpus (utf-8, utf-8-bom,
+    /// windows-1252). Accepted but unused, as jomini parses the raw bytes.
+    #[arg(long = "encoding")]
+    _encoding: Option<String>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -37,22 +44,14 @@ fn main() -> anyhow::Result<()> {
 
     match cli.task {
         Task::CanParse => {
-            let mut count = 0;
-            let mut reader = jomini::text::TokenReader::from_slice(&content);
-            loop {
-                match reader.next() {
-                    Ok(Some(_)) => {
-                        count += 1;
-                    }
-                    Ok(None) => break,
-                    Err(_) => {
-                        writeln!(output, "-1")?;
-                        break;
-                    }
+            let mut reader = TokenReader::from_slice(&content);
+            match document(&mut reader) {
+                Ok(document) => writeln!(output, "{}", document)?,
+                Err(e) => {
+                    writeln!(output, "rejected")?;
+                    writeln!(output, "{}", e)?;
                 }
             }
-
-            writeln!(output, "{}", count)?;
         }
         Task::Deserialization => {
             #[derive(Debug, JominiDeserialize)]
@@ -66,7 +65,7 @@ fn main() -> anyhow::Result<()> {
                 name: String,
             }
 
-            let reader = jomini::text::TokenReader::from_slice(&content["EU4txt".len()..]);
+            let reader = TokenReader::from_slice(&content["EU4txt".len()..]);
             let data: Gamestate = TextDeserializer::from_windows1252_reader(reader)
                 .deserialize()
                 .context("unable to deserialize")?;
@@ -87,7 +86,146 @@ fn main() -> anyhow::Result<()> {
     let stdout = std::io::stdout();
     let mut handle = stdout.lock();
     handle
-        .write_all(&output.get_ref())
+        .write_all(output.get_ref())
         .context("failed to write to stdout")?;
     Ok(())
 }
+
+/// An entry of an object or array while its container is being read
+enum Entry {
+    Value(Value),
+    Pair(String, Value),
+}
+
+/// A container whose closing brace hasn't been read yet
+struct Container {
+    /// Key and operator the container is the value of
+    key: Option<(String, Operator)>,
+    /// Name in front of the brace, like `rgb` in `color = rgb { 1 2 3 }`
+    tag: Option<String>,
+    entries: Vec<Entry>,
+}
+
+impl Container {
+    fn new(key: Option<(String, Operator)>, tag: Option<String>) -> Self {
+        Container {
+            key,
+            tag,
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// Read the document as JSON: containers of only `key=value` pairs become
+/// objects, with the values of repeated keys grouped into an array, and other
+/// containers become arrays, with any pairs in them as single key objects.
+/// Scalars are strings as written, and comparison operators wrap their value
+/// in an object named for the operator. Only the tokens decide whether the
+/// document is rejected, so stray braces and operators are skipped.
+fn document(reader: &mut TokenReader<&[u8]>) -> Result<Value, ReaderError> {
+    let mut stack = vec![Container::new(None, None)];
+    let mut pending: Option<(String, Operator)> = None;
+
+    loop {
+        let value = match reader.next()? {
+            None if stack.len() == 1 => break,
+            // Close what the document left open
+            None => close(&mut stack, &mut pending),
+            Some(Token::Quoted(scalar) | Token::Unquoted(scalar)) => {
+                Value::String(Windows1252Encoding::decode(scalar.as_bytes()).into_owned())
+            }
+            Some(Token::Operator(operator)) => {
+                let entries = &mut stack.last_mut().unwrap().entries;
+                match entries.pop() {
+                    Some(Entry::Value(Value::String(key))) if pending.is_none() => {
+                        pending = Some((key, operator));
+                    }
+                    entry => entries.extend(entry),
+                }
+                continue;
+            }
+            Some(Token::Open) => {
+                let entries = &mut stack.last_mut().unwrap().entries;
+                let container = match (pending.take(), entries.pop()) {
+                    (Some(key), entry) => {
+                        entries.extend(entry);
+                        Container::new(Some(key), None)
+                    }
+                    // A scalar directly before a brace is its key, or its tag
+                    // when the scalar is itself a value
+                    (None, Some(Entry::Value(Value::String(key)))) => {
+                        Container::new(Some((key, Operator::Equal)), None)
+                    }
+                    (None, Some(Entry::Pair(key, Value::String(tag)))) => {
+                        Container::new(Some((key, Operator::Equal)), Some(tag))
+                    }
+                    (None, entry) => {
+                        entries.extend(entry);
+                        Container::new(None, None)
+                    }
+                };
+                stack.push(container);
+                continue;
+            }
+            Some(Token::Close) if stack.len() == 1 => continue,
+            Some(Token::Close) => close(&mut stack, &mut pending),
+        };
+
+        let entry = match pending.take() {
+            Some((key, Operator::Equal)) => Entry::Pair(key, value),
+            Some((key, operator)) => {
+                let wrapped = Map::from_iter([(operator.name().to_string(), value)]);
+                Entry::Pair(key, Value::Object(wrapped))
+            }
+            None => Entry::Value(value),
+        };
+        stack.last_mut().unwrap().entries.push(entry);
+    }
+
+    Ok(container_value(stack.pop().unwrap().entries, true))
+}
+
+/// Close the innermost container, leaving its key pending for its value
+fn close(stack: &mut Vec<Container>, pending: &mut Option<(String, Operator)>) -> Value {
+    let container = stack.pop().unwrap();
+    *pending = container.key;
+    let value = container_value(container.entries, false);
+    match container.tag {
+        Some(tag) => Value::Object(Map::from_iter([(tag, value)])),
+        None => value,
+    }
+}
+
+fn container_value(entries: Vec<Entry>, root: bool) -> Value {
+    let is_object = entries.iter().all(|x| matches!(x, Entry::Pair(..)));
+    if !is_object || (entries.is_empty() && !root) {
+        let values = entries.into_iter().map(|entry| match entry {
+            Entry::Value(value) => value,
+            Entry::Pair(key, value) => Value::Object(Map::from_iter([(key, value)])),
+        });
+        return Value::Array(values.collect());
+    }
+
+    let mut keys: HashMap<String, usize> = HashMap::new();
+    let mut fields: Vec<(String, Vec<Value>)> = Vec::new();
+    for entry in entries {
+        let Entry::Pair(key, value) = entry else {
+            unreachable!("objects only hold pairs")
+        };
+        match keys.get(&key) {
+            Some(&index) => fields[index].1.push(value),
+            None => {
+                keys.insert(key.clone(), fields.len());
+                fields.push((key, vec![value]));
+            }
+        }
+    }
+
+    let object = fields
+        .into_iter()
+        .map(|(key, mut values)| match values.len() {
+            1 => (key, values.pop().unwrap()),
+            _ => (key, Value::Array(values)),
+        });
+    Value::Object(object.collect())
+}
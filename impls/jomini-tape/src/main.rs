@@ -1,5 +1,6 @@
 use anyhow::Context;
 use clap::{Parser, ValueEnum};
+use jomini::json::{DuplicateKeyMode, JsonOptions, TypeNarrowing};
 use jomini::{JominiDeserialize, TextTape};
 use serde::Deserialize;
 use std::io::{Cursor, Read, Write};
@@ -13,6 +14,10 @@ struct Cli {
     /// Game tag associated with this content (can be specified multiple times)
     #[arg(short, long, action = clap::ArgAction::Append)]
     game: Vec<String>,
+    /// Encoding of the content when declared by the corpus (utf-8, utf-8-bom,
+    /// windows-1252). Accepted but unused, as jomini parses the raw bytes.
+    #[arg(long = "encoding")]
+    _encoding: Option<String>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -38,10 +43,17 @@ fn main() -> anyhow::Result<()> {
     match cli.task {
         Task::CanParse => match TextTape::from_slice(&content) {
             Ok(tape) => {
-                writeln!(output, "{}", tape.tokens().len())?;
+                // Scalars are kept as written and repeated keys grouped, the
+                // document form the corpus expectations use
+                let options = JsonOptions::new()
+                    .with_type_narrowing(TypeNarrowing::None)
+                    .with_duplicate_keys(DuplicateKeyMode::Group);
+                let json = tape.windows1252_reader().json().with_options(options);
+                writeln!(output, "{}", json.to_string())?;
             }
-            Err(_) => {
-                writeln!(output, "-1")?;
+            Err(e) => {
+                writeln!(output, "rejected")?;
+                writeln!(output, "{}", e)?;
             }
         },
         Task::Deserialization => {
@@ -75,7 +87,7 @@ fn main() -> anyhow::Result<()> {
     let stdout = std::io::stdout();
     let mut handle = stdout.lock();
     handle
-        .write_all(&output.get_ref())
+        .write_all(output.get_ref())
         .context("failed to write to stdout")?;
     Ok(())
 }